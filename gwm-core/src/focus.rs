@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+
+/// The number of focus changes remembered by default.
+const DEFAULT_CAPACITY: usize = 32;
+
+/// A bounded history of focused clients, used to move back and forth between them.
+///
+/// Behaves like the history of a web browser: navigating backwards and focusing a client
+/// afterwards drops all entries after the current one. Focus changes caused by navigating the
+/// history itself should not be pushed.
+#[derive(Debug)]
+pub struct FocusHistory<C> {
+    /// The clients focused, oldest first.
+    entries: VecDeque<C>,
+    /// The index of the current entry, if any.
+    current: usize,
+    /// The maximal number of entries kept.
+    capacity: usize,
+}
+
+impl<C> Default for FocusHistory<C> {
+    fn default() -> Self {
+        FocusHistory::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl<C> FocusHistory<C> {
+    /// Create an empty history remembering the given number of focus changes.
+    pub fn new(capacity: usize) -> Self {
+        FocusHistory {
+            entries: VecDeque::with_capacity(capacity),
+            current: 0,
+            capacity: capacity.max(1),
+        }
+    }
+}
+
+impl<C: Clone + PartialEq> FocusHistory<C> {
+    /// Record that a client has been focused.
+    ///
+    /// If the client is the current entry already, nothing happens.
+    pub fn push(&mut self, client: C) {
+        if self.entries.get(self.current) == Some(&client) {
+            return;
+        }
+
+        self.entries.truncate(self.current + 1);
+        self.entries.push_back(client);
+
+        if self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+
+        self.current = self.entries.len() - 1;
+    }
+
+    /// Move to the closest earlier entry for which `exists` holds, and return it.
+    ///
+    /// Entries of clients that no longer exist are skipped. If there is no such entry, the
+    /// position in the history is left untouched.
+    pub fn back<F>(&mut self, exists: F) -> Option<C>
+        where F: Fn(&C) -> bool
+    {
+        let index = (0..self.current).rev().find(|&i| exists(&self.entries[i]))?;
+
+        self.current = index;
+        Some(self.entries[index].clone())
+    }
+
+    /// Move to the closest later entry for which `exists` holds, and return it.
+    ///
+    /// Entries of clients that no longer exist are skipped. If there is no such entry, the
+    /// position in the history is left untouched.
+    pub fn forward<F>(&mut self, exists: F) -> Option<C>
+        where F: Fn(&C) -> bool
+    {
+        let index = (self.current + 1..self.entries.len()).find(|&i| exists(&self.entries[i]))?;
+
+        self.current = index;
+        Some(self.entries[index].clone())
+    }
+
+    /// Remove all entries of a client, for instance when it is closed.
+    ///
+    /// If the current entry is removed, the entry before it becomes the current one.
+    pub fn remove(&mut self, client: &C) {
+        let before = self.entries.iter().take(self.current).filter(|c| *c == client).count();
+        let mut current = self.current - before;
+
+        if self.entries.get(self.current) == Some(client) {
+            current = current.saturating_sub(1);
+        }
+
+        self.entries.retain(|c| c != client);
+        self.current = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Construct a history holding the given clients, with the last one current.
+    fn history(clients: &[u32]) -> FocusHistory<u32> {
+        let mut history = FocusHistory::new(8);
+
+        for &client in clients {
+            history.push(client);
+        }
+
+        history
+    }
+
+    /// Get the current entry of a history.
+    fn current(history: &FocusHistory<u32>) -> Option<u32> {
+        history.entries.get(history.current).cloned()
+    }
+
+    #[test]
+    fn back_and_forward_stop_at_both_ends() {
+        let mut empty = FocusHistory::<u32>::default();
+        assert_eq!(empty.back(|_| true), None);
+        assert_eq!(empty.forward(|_| true), None);
+
+        let mut history = history(&[1, 2, 3]);
+        assert_eq!(history.forward(|_| true), None);
+        assert_eq!(current(&history), Some(3));
+
+        assert_eq!(history.back(|_| true), Some(2));
+        assert_eq!(history.back(|_| true), Some(1));
+        assert_eq!(history.back(|_| true), None);
+        assert_eq!(current(&history), Some(1));
+
+        // entries of clients that are gone are skipped
+        assert_eq!(history.forward(|&c| c != 2), Some(3));
+        assert_eq!(history.forward(|_| true), None);
+        assert_eq!(current(&history), Some(3));
+    }
+
+    #[test]
+    fn push_after_back_drops_forward_history() {
+        let mut history = history(&[1, 2, 3]);
+
+        assert_eq!(history.back(|_| true), Some(2));
+        history.push(2);
+        assert_eq!(history.entries, vec![1, 2, 3]);
+
+        history.push(4);
+        assert_eq!(history.entries, vec![1, 2, 4]);
+        assert_eq!(history.forward(|_| true), None);
+        assert_eq!(history.back(|_| true), Some(2));
+        assert_eq!(history.back(|_| true), Some(1));
+    }
+
+    #[test]
+    fn remove_keeps_current_entry() {
+        let mut history = history(&[1, 2, 3, 4]);
+        assert_eq!(history.back(|_| true), Some(3));
+
+        history.remove(&1);
+        assert_eq!(history.entries, vec![2, 3, 4]);
+        assert_eq!(current(&history), Some(3));
+
+        history.remove(&4);
+        assert_eq!(history.entries, vec![2, 3]);
+        assert_eq!(current(&history), Some(3));
+        assert_eq!(history.forward(|_| true), None);
+
+        // removing the current entry makes the entry before it current
+        history.remove(&3);
+        assert_eq!(history.entries, vec![2]);
+        assert_eq!(current(&history), Some(2));
+
+        history.remove(&2);
+        assert_eq!(current(&history), None);
+        assert_eq!(history.back(|_| true), None);
+    }
+}
@@ -0,0 +1,173 @@
+use layout::*;
+use tree::{ContainerId, Container, SplitType, TagTree};
+
+/// The binary space partitioning layout.
+///
+/// Each split container (and the root) holds at most two children, which divide the space
+/// available to their parent according to the split's type and ratio. New clients are
+/// inserted by splitting the container at the cursor in two, alternating the orientation
+/// of splits with the nesting depth.
+#[derive(Debug)]
+pub struct Bsp { }
+
+impl<C> Layout<C> for Bsp {
+    fn name(&self) -> &'static str { "bsp" }
+
+    fn render(&self,
+              tagtree: &TagTree<C>,
+              target: &Geometry,
+              gaps: &GapConfig,
+              sizes: &mut ClientSizes) {
+        fn handle_split<C>(tagtree: &TagTree<C>,
+                           current: ContainerId,
+                           split_type: SplitType,
+                           geo: Geometry,
+                           target: &Geometry,
+                           gaps: &GapConfig,
+                           sizes: &mut ClientSizes) {
+            let children: Vec<_> = tagtree.children(current).collect();
+
+            let geos = match (children.len(), split_type) {
+                (2, SplitType::Horizontal(ratio)) => {
+                    let (left, right) = geo.split_horizontal(ratio);
+                    vec![left, right]
+                },
+                (2, SplitType::Vertical(ratio)) => {
+                    let (top, bot) = geo.split_vertical(ratio);
+                    vec![top, bot]
+                },
+                (n, _) => vec![geo; n],
+            };
+
+            for ((child_id, child), geo) in children.into_iter().zip(geos) {
+                if child.floating() {
+                    continue;
+                }
+
+                match child {
+                    Container::Split(s) => handle_split(tagtree,
+                                                        ContainerId::Index(child_id),
+                                                        s.split_type,
+                                                        geo,
+                                                        target,
+                                                        gaps,
+                                                        sizes),
+                    Container::Client(_) => {
+                        sizes.insert(ContainerId::Index(child_id),
+                                     gaps.apply_inner(&geo, target));
+                    },
+                }
+            }
+        }
+
+        let target = &gaps.apply_outer(target);
+
+        handle_split(tagtree,
+                     ContainerId::Root,
+                     tagtree.root.split_type,
+                     *target,
+                     target,
+                     gaps,
+                     sizes);
+    }
+
+    fn check_tree(&self, tagtree: &TagTree<C>) -> bool {
+        tagtree.num_children(ContainerId::Root) <= 2 &&
+            tagtree.preorder(ContainerId::Root).all(|(id, c)| match c {
+                Container::Split(s) => s.split_type != SplitType::Tabbed &&
+                    tagtree.num_children(ContainerId::Index(id)) <= 2,
+                Container::Client(_) => true,
+            })
+    }
+
+    fn fixup_tree(&self, tagtree: &mut TagTree<C>) {
+        tagtree.binarize();
+    }
+
+    fn insert_client(&self, tagtree: &mut TagTree<C>, client: C) -> bool {
+        let cursor = if let Some(cursor) = tagtree.get_cursor() {
+            cursor
+        } else {
+            tagtree.insert_first_client(client);
+            return true;
+        };
+
+        let parent = tagtree.get_container(cursor).and_then(|c| c.get_parent());
+
+        match parent {
+            // the root can hold a second child without further splitting
+            Some(ContainerId::Root) if tagtree.num_children(ContainerId::Root) < 2 => {
+                tagtree.insert_client_after(cursor, client);
+            },
+            Some(parent) => {
+                let parent_split = match parent {
+                    ContainerId::Root => tagtree.root.split_type,
+                    ContainerId::Index(p) => match tagtree.get_container(p) {
+                        Some(Container::Split(s)) => s.split_type,
+                        _ => tagtree.root.split_type,
+                    },
+                };
+
+                tagtree.split_container(cursor, parent_split.flipped());
+                tagtree.insert_client_after(cursor, client);
+            },
+            None => return false,
+        }
+
+        true
+    }
+
+    fn insert_container(&self, tagtree: &mut TagTree<C>, src: &TagTree<C>, root: ContainerId)
+        -> bool
+    {
+        // TODO
+        false
+    }
+
+    fn delete_container(&self, tagtree: &mut TagTree<C>, container: ContainerId) -> bool {
+        let parent = match container {
+            ContainerId::Index(i) => tagtree.get_container(i).and_then(|c| c.get_parent()),
+            ContainerId::Root => None,
+        };
+
+        tagtree.delete_container(container);
+
+        // the sibling of the deleted container takes up the space of its former parent
+        if let Some(ContainerId::Index(p)) = parent {
+            if tagtree.get_container(p).is_some() {
+                tagtree.collapse_split(p);
+            }
+        }
+
+        true
+    }
+
+    fn find_container(&self,
+                      tagtree: &TagTree<C>,
+                      target: &Geometry,
+                      container: ContainerId,
+                      dir: Direction) -> Option<ContainerId> {
+        find_neighbour(self, tagtree, target, container, dir)
+    }
+
+    fn swap_containers(&self,
+                       tagtree: &mut TagTree<C>,
+                       a: ContainerId,
+                       b: ContainerId) -> bool {
+        tagtree.swap_clients(a, b)
+    }
+
+    fn move_container(&self,
+                      tagtree: &mut TagTree<C>,
+                      cursor: ContainerId,
+                      target: ContainerId) -> bool {
+        // TODO
+        false
+    }
+
+    fn rotate(&self, tagtree: &mut TagTree<C>, rotation: Rotation) -> bool {
+        rotate_tiled_clients(tagtree, rotation)
+    }
+
+    fn process_msg(&mut self, _: LayoutMessage) -> bool { false }
+}
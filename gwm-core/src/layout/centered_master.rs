@@ -0,0 +1,147 @@
+use std::cmp::min;
+
+use layout::*;
+use tree::{ContainerId, SplitRatio, TagTree};
+
+/// The centered master layout.
+///
+/// All clients are direct children of the root. The first client is placed in a column in the
+/// middle of the screen, with a width governed by the master factor. The remaining clients are
+/// stacked in two equally sized columns on either side of it, starting with the right one.
+/// With less than three clients, the master is placed on the left and the other client on the
+/// right.
+#[derive(Debug)]
+pub struct CenteredMaster {
+    /// The portion of the screen width taken up by the master column (parameter 0, in
+    /// permille).
+    master_factor: SplitRatio,
+}
+
+impl CenteredMaster {
+    /// Construct a new centered master layout using the given master factor.
+    pub fn new(master_factor: SplitRatio) -> CenteredMaster {
+        CenteredMaster { master_factor }
+    }
+}
+
+impl<C> Layout<C> for CenteredMaster {
+    fn name(&self) -> &'static str { "centered_master" }
+
+    fn render(&self,
+              tagtree: &TagTree<C>,
+              target: &Geometry,
+              gaps: &GapConfig,
+              sizes: &mut ClientSizes) {
+        let target = &gaps.apply_outer(target);
+        let clients = tiled_clients(tagtree);
+
+        match clients.len() {
+            0 => (),
+            1 => {
+                sizes.insert(clients[0], gaps.apply_inner(target, target));
+            },
+            2 => {
+                let (master, slave) = target.split_horizontal(self.master_factor);
+
+                sizes.insert(clients[0], gaps.apply_inner(&master, target));
+                sizes.insert(clients[1], gaps.apply_inner(&slave, target));
+            },
+            num_clients => {
+                let master_width = target.width * self.master_factor;
+                let left_width = (target.width - master_width) / 2;
+                let right_width = target.width - master_width - left_width;
+
+                let left = Geometry::new(target.x, target.y, left_width, target.height);
+                let master =
+                    Geometry::new(target.x + left_width, target.y, master_width, target.height);
+                let right = Geometry::new(target.x + left_width + master_width, target.y,
+                                          right_width, target.height);
+
+                sizes.insert(clients[0], gaps.apply_inner(&master, target));
+
+                // slaves alternate between the right and left column, starting on the right
+                let right_clients = clients[1..].iter().step_by(2);
+                let left_clients = clients[2..].iter().step_by(2);
+
+                let right_geos = right.split_vertical_n(num_clients / 2);
+                let left_geos = left.split_vertical_n((num_clients - 1) / 2);
+
+                for (client, geo) in right_clients.zip(right_geos) {
+                    sizes.insert(*client, gaps.apply_inner(&geo, target));
+                }
+
+                for (client, geo) in left_clients.zip(left_geos) {
+                    sizes.insert(*client, gaps.apply_inner(&geo, target));
+                }
+            },
+        }
+    }
+
+    fn check_tree(&self, tagtree: &TagTree<C>) -> bool {
+        is_flat(tagtree)
+    }
+
+    fn fixup_tree(&self, tagtree: &mut TagTree<C>) {
+        tagtree.flatten();
+    }
+
+    fn insert_client(&self, tagtree: &mut TagTree<C>, client: C) -> bool {
+        insert_client_flat(tagtree, client);
+
+        true
+    }
+
+    fn insert_container(&self, tagtree: &mut TagTree<C>, src: &TagTree<C>, root: ContainerId)
+        -> bool
+    {
+        // TODO
+        false
+    }
+
+    fn delete_container(&self, tagtree: &mut TagTree<C>, container: ContainerId) -> bool {
+        tagtree.delete_container(container);
+
+        true
+    }
+
+    fn find_container(&self,
+                      tagtree: &TagTree<C>,
+                      target: &Geometry,
+                      container: ContainerId,
+                      dir: Direction) -> Option<ContainerId> {
+        find_neighbour(self, tagtree, target, container, dir)
+    }
+
+    fn swap_containers(&self,
+                       tagtree: &mut TagTree<C>,
+                       a: ContainerId,
+                       b: ContainerId) -> bool {
+        tagtree.swap_clients(a, b)
+    }
+
+    fn move_container(&self,
+                      tagtree: &mut TagTree<C>,
+                      cursor: ContainerId,
+                      target: ContainerId) -> bool {
+        // TODO
+        false
+    }
+
+    fn rotate(&self, tagtree: &mut TagTree<C>, rotation: Rotation) -> bool {
+        rotate_tiled_clients(tagtree, rotation)
+    }
+
+    fn process_msg(&mut self, msg: LayoutMessage) -> bool {
+        match msg {
+            LayoutMessage::ParamAbs { id: 0, value } => {
+                self.master_factor = SplitRatio::from_permille(value);
+                true
+            },
+            LayoutMessage::ParamAdd { id: 0, inc } => {
+                self.master_factor = self.master_factor + min(inc, 1000) as u16;
+                true
+            },
+            _ => false,
+        }
+    }
+}
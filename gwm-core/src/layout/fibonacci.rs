@@ -0,0 +1,173 @@
+use std::cmp::min;
+
+use layout::*;
+use tree::{ContainerId, SplitRatio, TagTree};
+
+/// The fibonacci layout.
+///
+/// All clients are direct children of the root. Each client takes up a part of the area left
+/// over by its predecessors, with the side it is placed on rotating clockwise, yielding a
+/// self-similar spiral. The first split is governed by the master factor, all others halve
+/// the remaining area.
+#[derive(Debug)]
+pub struct Fibonacci {
+    /// The ratio of the first split (parameter 0, in permille).
+    master_factor: SplitRatio,
+}
+
+impl Fibonacci {
+    /// Construct a new fibonacci layout using the given master factor.
+    pub fn new(master_factor: SplitRatio) -> Fibonacci {
+        Fibonacci { master_factor }
+    }
+}
+
+impl<C> Layout<C> for Fibonacci {
+    fn name(&self) -> &'static str { "fibonacci" }
+
+    fn render(&self,
+              tagtree: &TagTree<C>,
+              target: &Geometry,
+              gaps: &GapConfig,
+              sizes: &mut ClientSizes) {
+        let target = &gaps.apply_outer(target);
+        let clients = tiled_clients(tagtree);
+        let num_clients = clients.len();
+        let mut rest = *target;
+
+        for (i, client) in clients.into_iter().enumerate() {
+            if i + 1 == num_clients {
+                sizes.insert(client, gaps.apply_inner(&rest, target));
+                break;
+            }
+
+            let ratio = if i == 0 { self.master_factor } else { SplitRatio::new(50) };
+
+            // the client is placed left, top, right, and bottom of the remaining area in turn
+            let (geo, rest_prime) = match i % 4 {
+                0 => rest.split_horizontal(ratio),
+                1 => rest.split_vertical(ratio),
+                2 => {
+                    let (rest_prime, geo) = rest.split_horizontal(ratio);
+                    (geo, rest_prime)
+                },
+                _ => {
+                    let (rest_prime, geo) = rest.split_vertical(ratio);
+                    (geo, rest_prime)
+                },
+            };
+
+            sizes.insert(client, gaps.apply_inner(&geo, target));
+            rest = rest_prime;
+        }
+    }
+
+    fn check_tree(&self, tagtree: &TagTree<C>) -> bool {
+        is_flat(tagtree)
+    }
+
+    fn fixup_tree(&self, tagtree: &mut TagTree<C>) {
+        tagtree.flatten();
+    }
+
+    fn insert_client(&self, tagtree: &mut TagTree<C>, client: C) -> bool {
+        insert_client_flat(tagtree, client);
+
+        true
+    }
+
+    fn insert_container(&self, tagtree: &mut TagTree<C>, src: &TagTree<C>, root: ContainerId)
+        -> bool
+    {
+        // TODO
+        false
+    }
+
+    fn delete_container(&self, tagtree: &mut TagTree<C>, container: ContainerId) -> bool {
+        tagtree.delete_container(container);
+
+        true
+    }
+
+    fn find_container(&self,
+                      tagtree: &TagTree<C>,
+                      target: &Geometry,
+                      container: ContainerId,
+                      dir: Direction) -> Option<ContainerId> {
+        find_neighbour(self, tagtree, target, container, dir)
+    }
+
+    fn swap_containers(&self,
+                       tagtree: &mut TagTree<C>,
+                       a: ContainerId,
+                       b: ContainerId) -> bool {
+        tagtree.swap_clients(a, b)
+    }
+
+    fn move_container(&self,
+                      tagtree: &mut TagTree<C>,
+                      cursor: ContainerId,
+                      target: ContainerId) -> bool {
+        // TODO
+        false
+    }
+
+    fn rotate(&self, tagtree: &mut TagTree<C>, rotation: Rotation) -> bool {
+        rotate_tiled_clients(tagtree, rotation)
+    }
+
+    fn process_msg(&mut self, msg: LayoutMessage) -> bool {
+        match msg {
+            LayoutMessage::ParamAbs { id: 0, value } => {
+                self.master_factor = SplitRatio::from_permille(value);
+                true
+            },
+            LayoutMessage::ParamAdd { id: 0, inc } => {
+                self.master_factor = self.master_factor + min(inc, 1000) as u16;
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree::SplitType;
+
+    #[test]
+    fn clients_tile_target_without_overlap() {
+        let layout = Fibonacci::new(SplitRatio::new(60));
+        let target = Geometry::new(0, 0, 1280, 800);
+
+        for n in 1..=8 {
+            let mut tree = TagTree::new(SplitType::Horizontal(SplitRatio::new(50)));
+            let mut sizes = ClientSizes::new();
+
+            for client in 0..n {
+                Layout::<u32>::insert_client(&layout, &mut tree, client);
+            }
+
+            layout.render(&tree, &target, &GapConfig::none(), &mut sizes);
+            assert_eq!(sizes.len(), n as usize);
+
+            let geos: Vec<_> = sizes.values().collect();
+            let mut area = 0;
+
+            for (i, geo) in geos.iter().enumerate() {
+                assert!(geo.x + geo.width <= target.width && geo.y + geo.height <= target.height,
+                        "{} clients: {:?} exceeds the target", n, geo);
+
+                for other in &geos[i + 1..] {
+                    assert!(!geo.intersects(other),
+                            "{} clients: {:?} overlaps {:?}", n, geo, other);
+                }
+
+                area += geo.width * geo.height;
+            }
+
+            assert_eq!(area, target.width * target.height, "{} clients leave gaps", n);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use layout::*;
+use tree::{ContainerId, TagTree};
+
+/// The grid layout.
+///
+/// All clients are direct children of the root and are placed in the cells of the smallest
+/// grid that is (roughly) square and has enough cells to hold all clients. Cells are filled
+/// either row by row, or column by column.
+#[derive(Debug)]
+pub struct Grid {
+    /// Whether to fill the grid row by row (or column by column otherwise).
+    prefer_rows: bool,
+}
+
+impl Grid {
+    /// Construct a new grid layout filling the grid in the given order.
+    pub fn new(prefer_rows: bool) -> Grid {
+        Grid { prefer_rows }
+    }
+
+    /// Compute the number of rows and columns of the grid used to hold a number of clients.
+    fn dimensions(&self, num_clients: usize) -> (usize, usize) {
+        let mut major = 1;
+
+        while major * major < num_clients {
+            major += 1;
+        }
+
+        let minor = num_clients.div_ceil(major);
+
+        if self.prefer_rows {
+            (minor, major)
+        } else {
+            (major, minor)
+        }
+    }
+
+    /// Get the row and column of the cell holding the client with the given index.
+    fn cell(&self, index: usize, rows: usize, cols: usize) -> (usize, usize) {
+        if self.prefer_rows {
+            (index / cols, index % cols)
+        } else {
+            (index % rows, index / rows)
+        }
+    }
+
+    /// Get the index of the client held in the given cell, if any.
+    fn index(&self, row: usize, col: usize, rows: usize, cols: usize, num_clients: usize)
+        -> Option<usize>
+    {
+        let index = if self.prefer_rows { row * cols + col } else { col * rows + row };
+
+        if row < rows && col < cols && index < num_clients {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl<C> Layout<C> for Grid {
+    fn name(&self) -> &'static str { "grid" }
+
+    fn render(&self,
+              tagtree: &TagTree<C>,
+              target: &Geometry,
+              gaps: &GapConfig,
+              sizes: &mut ClientSizes) {
+        let target = &gaps.apply_outer(target);
+        let clients = tiled_clients(tagtree);
+
+        if clients.is_empty() {
+            return;
+        }
+
+        let (rows, cols) = self.dimensions(clients.len());
+        let (cell, width) = target.split_horizontal_eq(cols);
+        let (cell, height) = cell.split_vertical_eq(rows);
+
+        for (i, client) in clients.into_iter().enumerate() {
+            let (row, col) = self.cell(i, rows, cols);
+            let mut geo = cell
+                .x_offset((col as u32 * width) as i32)
+                .y_offset((row as u32 * height) as i32);
+
+            // the last row and column take up the space left over by the integer division
+            if col + 1 == cols {
+                geo.width = target.width - col as u32 * width;
+            }
+
+            if row + 1 == rows {
+                geo.height = target.height - row as u32 * height;
+            }
+
+            sizes.insert(client, gaps.apply_inner(&geo, target));
+        }
+    }
+
+    fn check_tree(&self, tagtree: &TagTree<C>) -> bool {
+        is_flat(tagtree)
+    }
+
+    fn fixup_tree(&self, tagtree: &mut TagTree<C>) {
+        tagtree.flatten();
+    }
+
+    fn insert_client(&self, tagtree: &mut TagTree<C>, client: C) -> bool {
+        insert_client_flat(tagtree, client);
+
+        true
+    }
+
+    fn insert_container(&self, tagtree: &mut TagTree<C>, src: &TagTree<C>, root: ContainerId)
+        -> bool
+    {
+        // TODO
+        false
+    }
+
+    fn delete_container(&self, tagtree: &mut TagTree<C>, container: ContainerId) -> bool {
+        tagtree.delete_container(container);
+
+        true
+    }
+
+    fn find_container(&self,
+                      tagtree: &TagTree<C>,
+                      target: &Geometry,
+                      container: ContainerId,
+                      dir: Direction) -> Option<ContainerId> {
+        let clients = tiled_clients(tagtree);
+        let num_clients = clients.len();
+        let index = clients.iter().position(|c| *c == container)?;
+        let (rows, cols) = self.dimensions(num_clients);
+        let (row, col) = self.cell(index, rows, cols);
+
+        let target = match dir {
+            // moving past the end of a row wraps around to the next one and vice versa
+            Direction::Right => self
+                .index(row, col + 1, rows, cols, num_clients)
+                .or_else(|| self.index(row + 1, 0, rows, cols, num_clients)),
+            Direction::Left if col > 0 => self.index(row, col - 1, rows, cols, num_clients),
+            Direction::Left if row > 0 => (0..cols)
+                .rev()
+                .filter_map(|c| self.index(row - 1, c, rows, cols, num_clients))
+                .next(),
+            Direction::Left => None,
+            Direction::Down => self.index(row + 1, col, rows, cols, num_clients),
+            Direction::Up if row > 0 => self.index(row - 1, col, rows, cols, num_clients),
+            Direction::Up => None,
+            dir => return find_neighbour(self, tagtree, target, container, dir),
+        };
+
+        target.map(|i| clients[i])
+    }
+
+    fn swap_containers(&self,
+                       tagtree: &mut TagTree<C>,
+                       a: ContainerId,
+                       b: ContainerId) -> bool {
+        tagtree.swap_clients(a, b)
+    }
+
+    fn move_container(&self,
+                      tagtree: &mut TagTree<C>,
+                      cursor: ContainerId,
+                      target: ContainerId) -> bool {
+        // TODO
+        false
+    }
+
+    fn rotate(&self, tagtree: &mut TagTree<C>, rotation: Rotation) -> bool {
+        rotate_tiled_clients(tagtree, rotation)
+    }
+
+    fn process_msg(&mut self, _: LayoutMessage) -> bool { false }
+}
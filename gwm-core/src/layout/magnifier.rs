@@ -0,0 +1,196 @@
+use std::cmp::min;
+
+use layout::*;
+use tree::{ContainerId, SplitRatio, TagTree};
+
+/// The edge of the screen along which the magnifier layout places unfocused clients.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MagnifierEdge {
+    /// Place unfocused clients along the top edge.
+    Top,
+    /// Place unfocused clients along the bottom edge.
+    Bottom,
+    /// Place unfocused clients along the left edge.
+    Left,
+    /// Place unfocused clients along the right edge.
+    Right,
+}
+
+/// The magnifier layout.
+///
+/// All clients are direct children of the root. The focused client takes up most of the
+/// screen, while all other clients are stacked in a strip along one edge, keeping their
+/// order. Focusing another client moves it to the large area on the next render.
+#[derive(Debug)]
+pub struct Magnifier {
+    /// The percentage of the screen taken up by the focused client (parameter 0).
+    zoom: u8,
+    /// The edge along which unfocused clients are placed.
+    edge: MagnifierEdge,
+}
+
+impl Magnifier {
+    /// Construct a new magnifier layout using the given zoom percentage and edge.
+    pub fn new(zoom: u8, edge: MagnifierEdge) -> Magnifier {
+        Magnifier {
+            zoom: min(zoom, 100),
+            edge,
+        }
+    }
+
+    /// Split the given geometry into the area of the focused client and the strip holding all
+    /// other clients.
+    fn split(&self, target: &Geometry) -> (Geometry, Geometry) {
+        let zoom = SplitRatio::new(self.zoom);
+        let rest = SplitRatio::new(100 - self.zoom);
+
+        match self.edge {
+            MagnifierEdge::Top => {
+                let (strip, main) = target.split_vertical(rest);
+                (main, strip)
+            },
+            MagnifierEdge::Bottom => target.split_vertical(zoom),
+            MagnifierEdge::Left => {
+                let (strip, main) = target.split_horizontal(rest);
+                (main, strip)
+            },
+            MagnifierEdge::Right => target.split_horizontal(zoom),
+        }
+    }
+}
+
+impl<C> Layout<C> for Magnifier {
+    fn name(&self) -> &'static str { "magnifier" }
+
+    fn render(&self,
+              tagtree: &TagTree<C>,
+              target: &Geometry,
+              gaps: &GapConfig,
+              sizes: &mut ClientSizes) {
+        let target = &gaps.apply_outer(target);
+        let mut clients = tiled_clients(tagtree);
+
+        if clients.is_empty() {
+            return;
+        }
+
+        let focused = tagtree.root.get_focused().map(ContainerId::Index);
+        let index = clients.iter().position(|c| Some(*c) == focused).unwrap_or(0);
+        let magnified = clients.remove(index);
+
+        if clients.is_empty() {
+            sizes.insert(magnified, gaps.apply_inner(target, target));
+            return;
+        }
+
+        let (main, strip) = self.split(target);
+        let strip_geos = match self.edge {
+            MagnifierEdge::Top | MagnifierEdge::Bottom => strip.split_horizontal_n(clients.len()),
+            MagnifierEdge::Left | MagnifierEdge::Right => strip.split_vertical_n(clients.len()),
+        };
+
+        sizes.insert(magnified, gaps.apply_inner(&main, target));
+
+        for (client, geo) in clients.into_iter().zip(strip_geos) {
+            sizes.insert(client, gaps.apply_inner(&geo, target));
+        }
+    }
+
+    fn check_tree(&self, tagtree: &TagTree<C>) -> bool {
+        is_flat(tagtree)
+    }
+
+    fn fixup_tree(&self, tagtree: &mut TagTree<C>) {
+        tagtree.flatten();
+    }
+
+    fn insert_client(&self, tagtree: &mut TagTree<C>, client: C) -> bool {
+        insert_client_flat(tagtree, client);
+
+        true
+    }
+
+    fn insert_container(&self, tagtree: &mut TagTree<C>, src: &TagTree<C>, root: ContainerId)
+        -> bool
+    {
+        // TODO
+        false
+    }
+
+    fn delete_container(&self, tagtree: &mut TagTree<C>, container: ContainerId) -> bool {
+        tagtree.delete_container(container);
+
+        true
+    }
+
+    fn find_container(&self,
+                      tagtree: &TagTree<C>,
+                      target: &Geometry,
+                      container: ContainerId,
+                      dir: Direction) -> Option<ContainerId> {
+        find_neighbour(self, tagtree, target, container, dir)
+    }
+
+    fn swap_containers(&self,
+                       tagtree: &mut TagTree<C>,
+                       a: ContainerId,
+                       b: ContainerId) -> bool {
+        tagtree.swap_clients(a, b)
+    }
+
+    fn move_container(&self,
+                      tagtree: &mut TagTree<C>,
+                      cursor: ContainerId,
+                      target: ContainerId) -> bool {
+        // TODO
+        false
+    }
+
+    fn rotate(&self, tagtree: &mut TagTree<C>, rotation: Rotation) -> bool {
+        rotate_tiled_clients(tagtree, rotation)
+    }
+
+    fn process_msg(&mut self, msg: LayoutMessage) -> bool {
+        match msg {
+            LayoutMessage::ParamAbs { id: 0, value } => {
+                self.zoom = min(value, 100) as u8;
+                true
+            },
+            LayoutMessage::ParamAdd { id: 0, inc } => {
+                self.zoom = min((self.zoom as usize).saturating_add(inc), 100) as u8;
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_stays_in_bounds() {
+        let mut layout = Magnifier::new(60, MagnifierEdge::Bottom);
+
+        assert!(Layout::<u32>::process_msg(&mut layout,
+                                            LayoutMessage::ParamAbs { id: 0, value: 100 }));
+        assert_eq!(layout.zoom, 100);
+
+        assert!(Layout::<u32>::process_msg(&mut layout,
+                                            LayoutMessage::ParamAbs { id: 0, value: usize::MAX }));
+        assert_eq!(layout.zoom, 100);
+
+        assert!(Layout::<u32>::process_msg(&mut layout,
+                                            LayoutMessage::ParamAbs { id: 0, value: 0 }));
+        assert_eq!(layout.zoom, 0);
+
+        assert!(Layout::<u32>::process_msg(&mut layout,
+                                            LayoutMessage::ParamAdd { id: 0, inc: 100 }));
+        assert_eq!(layout.zoom, 100);
+
+        assert!(Layout::<u32>::process_msg(&mut layout,
+                                            LayoutMessage::ParamAdd { id: 0, inc: usize::MAX }));
+        assert_eq!(layout.zoom, 100);
+    }
+}
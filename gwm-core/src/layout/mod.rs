@@ -2,15 +2,27 @@
 #![allow(unused_variables)]
 #![allow(dead_code)]
 
+use std::cmp;
 use std::collections::HashMap;
 use std::fmt::Debug;
 
 use tree::{ArenaContainerId, ContainerId, Container, SplitRatio, SplitType, TagTree};
 
+pub mod bsp;
+pub mod centered_master;
+pub mod fibonacci;
+pub mod grid;
+pub mod magnifier;
+pub mod reflected;
+pub mod tabbed;
+pub mod tricolumn;
+
+pub use self::reflected::Reflected;
+
 /// A rectangle somewhere on screen.
 ///
 /// Could represent a client's geometry, a screen, or something else.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Geometry {
     /// The x coordinate of the upper left corner of the rectangle.
     x: u32,
@@ -23,11 +35,16 @@ pub struct Geometry {
 }
 
 impl Geometry {
+    /// Construct a new geometry from its upper left corner and its dimensions.
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Geometry {
+        Geometry { x, y, width, height }
+    }
+
     /// Split the given geometry horizontally in two.
     ///
     /// Return a pair of subgeometries (left first) computed in the split.
     pub fn split_horizontal(&self, ratio: SplitRatio) -> (Geometry, Geometry) {
-        let width_prime = self.height * ratio;
+        let width_prime = self.width * ratio;
         let x_prime = self.x + width_prime;
 
         let left = Geometry {
@@ -103,6 +120,42 @@ impl Geometry {
         (top, height_prime)
     }
 
+    /// Split the given geometry vertically in `n` subgeometries.
+    ///
+    /// Returns the subgeometries from top to bottom. The last subgeometry takes up the space
+    /// left over by the integer division, so that the given geometry is covered completely.
+    pub fn split_vertical_n(&self, n: usize) -> Vec<Geometry> {
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let (top, height_prime) = self.split_vertical_eq(n);
+        let mut res: Vec<_> =
+            (0..n).map(|i| top.y_offset((i as u32 * height_prime) as i32)).collect();
+
+        res[n - 1].height = self.height - (n as u32 - 1) * height_prime;
+
+        res
+    }
+
+    /// Split the given geometry horizontally in `n` subgeometries.
+    ///
+    /// Returns the subgeometries from left to right. The last subgeometry takes up the space
+    /// left over by the integer division, so that the given geometry is covered completely.
+    pub fn split_horizontal_n(&self, n: usize) -> Vec<Geometry> {
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let (left, width_prime) = self.split_horizontal_eq(n);
+        let mut res: Vec<_> =
+            (0..n).map(|i| left.x_offset((i as u32 * width_prime) as i32)).collect();
+
+        res[n - 1].width = self.width - (n as u32 - 1) * width_prime;
+
+        res
+    }
+
     /// Move the given geometry by the given offset in x direction.
     ///
     /// Returns the moved geometry.
@@ -136,8 +189,403 @@ impl Geometry {
     }
 
     pub fn center(&mut self, reference: &Geometry) {
-        self.x = reference.x + (reference.width / 2) - (self.width / 2);
-        self.y = reference.y + (reference.height / 2) - (self.height / 2);
+        *self = Geometry::centered_on(reference, self.width, self.height);
+    }
+
+    /// Get the point in the middle of the given geometry.
+    pub fn center_point(&self) -> (u32, u32) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    /// Construct a geometry of the given size, centered within the given container.
+    ///
+    /// The size is clamped to the container's, so that the result never exceeds its bounds.
+    pub fn centered_on(container: &Geometry, width: u32, height: u32) -> Geometry {
+        let width = width.min(container.width);
+        let height = height.min(container.height);
+
+        Geometry {
+            x: container.x + (container.width - width) / 2,
+            y: container.y + (container.height - height) / 2,
+            width,
+            height,
+        }
+    }
+
+    /// Move the given geometry by the given offsets in both directions.
+    ///
+    /// Returns the moved geometry, with coordinates clamped to the positive quadrant.
+    pub fn translated(&self, dx: i32, dy: i32) -> Geometry {
+        let clamp = |c: u32, d: i32| (i64::from(c) + i64::from(d)).max(0) as u32;
+
+        Geometry {
+            x: clamp(self.x, dx),
+            y: clamp(self.y, dy),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Move the given geometry so that it lies within the given bounds, if possible.
+    ///
+    /// The size of the geometry is kept. If it is larger than the bounds in one dimension, it
+    /// is aligned with their upper or left edge.
+    pub fn clamped_to(&self, bounds: &Geometry) -> Geometry {
+        let clamp = |pos: u32, len: u32, min: u32, bound: u32| {
+            pos.min((min + bound).saturating_sub(len)).max(min)
+        };
+
+        Geometry {
+            x: clamp(self.x, self.width, bounds.x, bounds.width),
+            y: clamp(self.y, self.height, bounds.y, bounds.height),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Grow or shrink the given geometry by the given amounts in both dimensions.
+    ///
+    /// Returns the resized geometry with the same upper left corner, at least one pixel in
+    /// either dimension.
+    pub fn resized(&self, dw: i32, dh: i32) -> Geometry {
+        let clamp = |l: u32, d: i32| (i64::from(l) + i64::from(d)).max(1) as u32;
+
+        Geometry {
+            x: self.x,
+            y: self.y,
+            width: clamp(self.width, dw),
+            height: clamp(self.height, dh),
+        }
+    }
+
+    /// Remove the space reserved by the given struts from the geometry.
+    ///
+    /// Returns the area available to clients.
+    pub fn subtract_struts(&self, struts: &Struts) -> Geometry {
+        let (left, right) = (u32::from(struts.left), u32::from(struts.right));
+        let (top, bottom) = (u32::from(struts.top), u32::from(struts.bottom));
+
+        Geometry {
+            x: self.x + left,
+            y: self.y + top,
+            width: self.width.saturating_sub(left + right),
+            height: self.height.saturating_sub(top + bottom),
+        }
+    }
+
+    /// Mirror the given geometry within a reference geometry along the given axis.
+    ///
+    /// Geometries lying within the reference geometry stay within it. Coordinates of other
+    /// geometries that would be mirrored past the origin are clamped to zero.
+    pub fn reflected(&self, reference: &Geometry, axis: Axis) -> Geometry {
+        match axis {
+            Axis::Horizontal => Geometry {
+                x: (2 * reference.x + reference.width).saturating_sub(self.x + self.width),
+                ..*self
+            },
+            Axis::Vertical => Geometry {
+                y: (2 * reference.y + reference.height).saturating_sub(self.y + self.height),
+                ..*self
+            },
+        }
+    }
+
+    /// Check whether the given point lies within the geometry.
+    pub fn contains_point(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x - self.x < self.width && y >= self.y && y - self.y < self.height
+    }
+
+    /// Check whether the given geometries overlap.
+    ///
+    /// Geometries that merely touch along an edge are not considered to be overlapping.
+    pub fn intersects(&self, other: &Geometry) -> bool {
+        self.x < other.x + other.width && other.x < self.x + self.width &&
+            self.y < other.y + other.height && other.y < self.y + self.height
+    }
+}
+
+/// Space reserved by panels and docks at the edges of a screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Struts {
+    /// The space reserved at the left edge.
+    pub left: u16,
+    /// The space reserved at the right edge.
+    pub right: u16,
+    /// The space reserved at the top edge.
+    pub top: u16,
+    /// The space reserved at the bottom edge.
+    pub bottom: u16,
+}
+
+impl Struts {
+    /// Combine the struts of two panels, reserving enough space for both on each edge.
+    pub fn combine(&self, other: &Struts) -> Struts {
+        Struts {
+            left: self.left.max(other.left),
+            right: self.right.max(other.right),
+            top: self.top.max(other.top),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+}
+
+/// Gaps to leave between clients, and between clients and the screen edges.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GapConfig {
+    /// The gap between clients and the edges of the rendered area.
+    pub outer_gap: u16,
+    /// The gap between neighbouring clients.
+    pub inner_gap: u16,
+}
+
+impl GapConfig {
+    /// Construct a gap configuration leaving no gaps at all.
+    pub fn none() -> GapConfig {
+        GapConfig::default()
+    }
+
+    /// Shrink the area to render on by the outer gap.
+    pub fn apply_outer(&self, target: &Geometry) -> Geometry {
+        let gap = u32::from(self.outer_gap);
+
+        Geometry {
+            x: target.x + gap,
+            y: target.y + gap,
+            width: target.width.saturating_sub(2 * gap),
+            height: target.height.saturating_sub(2 * gap),
+        }
+    }
+
+    /// Shrink a client's geometry by the inner gap on all sides facing other clients.
+    ///
+    /// Sides touching the edges of the area rendered on (as returned by `apply_outer`) are left
+    /// untouched, so that neighbouring clients end up exactly one inner gap apart.
+    pub fn apply_inner(&self, geo: &Geometry, target: &Geometry) -> Geometry {
+        let gap = u32::from(self.inner_gap);
+        let left = if geo.x > target.x { gap / 2 } else { 0 };
+        let top = if geo.y > target.y { gap / 2 } else { 0 };
+        let right =
+            if geo.x + geo.width < target.x + target.width { gap - gap / 2 } else { 0 };
+        let bottom =
+            if geo.y + geo.height < target.y + target.height { gap - gap / 2 } else { 0 };
+
+        Geometry {
+            x: geo.x + left,
+            y: geo.y + top,
+            width: geo.width.saturating_sub(left + right),
+            height: geo.height.saturating_sub(top + bottom),
+        }
+    }
+
+    /// Adjust the inner gap by the given amount.
+    pub fn adjust_inner(&mut self, delta: i16) {
+        self.inner_gap = adjust_gap(self.inner_gap, delta);
+    }
+
+    /// Adjust the outer gap by the given amount.
+    pub fn adjust_outer(&mut self, delta: i16) {
+        self.outer_gap = adjust_gap(self.outer_gap, delta);
+    }
+
+    /// Limit both gaps to a quarter of the smaller dimension of the area to render on.
+    pub fn clamped(&self, target: &Geometry) -> GapConfig {
+        let max = cmp::min(target.width, target.height) / 4;
+        let clamp = |gap: u16| cmp::min(u32::from(gap), max) as u16;
+
+        GapConfig {
+            outer_gap: clamp(self.outer_gap),
+            inner_gap: clamp(self.inner_gap),
+        }
+    }
+}
+
+/// Window borders drawn around clients, and their colours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Decorations {
+    /// The width of the border drawn around each client, in pixels.
+    pub border_width: u16,
+    /// The border colour of the focused client.
+    pub focus_color: u32,
+    /// The border colour of all other clients.
+    pub normal_color: u32,
+    /// The border colour of urgent clients.
+    pub urgent_color: u32,
+}
+
+impl Default for Decorations {
+    fn default() -> Decorations {
+        Decorations {
+            border_width: 0,
+            focus_color: 0x00ff_ffff,
+            normal_color: 0x0044_4444,
+            urgent_color: 0x00ff_0000,
+        }
+    }
+}
+
+impl Decorations {
+    /// Shrink a client's geometry so that it fits into the given one including its border.
+    ///
+    /// X draws borders outside a window's size, but at its position, so only the size is
+    /// changed. At least one pixel is left in either dimension.
+    pub fn apply_border(&self, geo: &Geometry) -> Geometry {
+        let border = 2 * u32::from(self.border_width);
+
+        Geometry {
+            x: geo.x,
+            y: geo.y,
+            width: geo.width.saturating_sub(border).max(1),
+            height: geo.height.saturating_sub(border).max(1),
+        }
+    }
+}
+
+/// Adjust a gap by the given amount, without underflowing.
+fn adjust_gap(gap: u16, delta: i16) -> u16 {
+    if delta < 0 {
+        gap.saturating_sub(delta.unsigned_abs())
+    } else {
+        gap.saturating_add(delta as u16)
+    }
+}
+
+/// Size constraints requested by a client, as given by its `WM_NORMAL_HINTS` property.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SizeHints {
+    /// The minimal width of the client, also used as the base for width increments.
+    pub min_width: u32,
+    /// The minimal height of the client, also used as the base for height increments.
+    pub min_height: u32,
+    /// The maximal width of the client, if any.
+    pub max_width: Option<u32>,
+    /// The maximal height of the client, if any.
+    pub max_height: Option<u32>,
+    /// The width increment of the client (for instance, the width of a terminal's cell).
+    pub width_inc: u32,
+    /// The height increment of the client (for instance, the height of a terminal's cell).
+    pub height_inc: u32,
+}
+
+/// Shrink a geometry to the closest size allowed by a client's size hints.
+///
+/// The resulting geometry is centered in the original one, so that the leftover space is
+/// distributed evenly around the client. Since clients can't be made larger than the space
+/// they are given, minimal sizes are only used as the base for increments.
+pub fn constrain_geometry(geo: &Geometry, hints: &SizeHints) -> Geometry {
+    fn constrain(len: u32, min: u32, max: Option<u32>, inc: u32) -> u32 {
+        let len = max.map_or(len, |m| len.min(m));
+
+        if inc > 1 && len > min {
+            len - (len - min) % inc
+        } else {
+            len
+        }
+    }
+
+    let width = constrain(geo.width, hints.min_width, hints.max_width, hints.width_inc);
+    let height = constrain(geo.height, hints.min_height, hints.max_height, hints.height_inc);
+
+    Geometry::centered_on(geo, width, height)
+}
+
+/// Resize a floating client's geometry by the given amounts, honoring its size hints.
+///
+/// Unlike `constrain_geometry`, the upper left corner of the geometry is kept in place, so
+/// that the client grows and shrinks towards its lower right corner.
+pub fn resize_constrained(geo: &Geometry, dw: i32, dh: i32, hints: Option<&SizeHints>)
+    -> Geometry
+{
+    let mut res = geo.resized(dw, dh);
+
+    if let Some(hints) = hints {
+        let constrained = constrain_geometry(&res, hints);
+        res.width = constrained.width;
+        res.height = constrained.height;
+    }
+
+    res
+}
+
+/// Thresholds used to snap floating clients to screen edges and other clients.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapConfig {
+    /// The distance in pixels from which clients snap to the edges of the screen.
+    pub edge_threshold: u16,
+    /// The distance in pixels from which clients snap to the edges of other clients.
+    pub window_threshold: u16,
+    /// Whether snapping is performed at all.
+    pub enabled: bool,
+}
+
+impl Default for SnapConfig {
+    fn default() -> SnapConfig {
+        SnapConfig {
+            edge_threshold: 8,
+            window_threshold: 8,
+            enabled: true,
+        }
+    }
+}
+
+impl SnapConfig {
+    /// Snap a floating client's geometry to the screen and other floating clients.
+    ///
+    /// Each axis is snapped independently to the closest edge within the respective threshold,
+    /// aligning either of the client's edges with it. The edges of other clients are only
+    /// considered if the two geometries overlap on the other axis. The size of the client is
+    /// never changed.
+    pub fn snap(&self, geo: &Geometry, screen: &Geometry, others: &[Geometry]) -> Geometry {
+        /// Compute the snapped position of an interval given candidate edges and thresholds.
+        fn snap_axis(pos: u32, len: u32, edges: &[(u32, u32)]) -> u32 {
+            let mut best: Option<(u32, u32)> = None;
+
+            for &(edge, threshold) in edges {
+                let mut candidates = vec![edge];
+
+                if edge >= len {
+                    candidates.push(edge - len);
+                }
+
+                for new_pos in candidates {
+                    let dist = (i64::from(new_pos) - i64::from(pos)).unsigned_abs() as u32;
+
+                    if dist <= threshold && best.is_none_or(|(_, d)| dist < d) {
+                        best = Some((new_pos, dist));
+                    }
+                }
+            }
+
+            best.map_or(pos, |(p, _)| p)
+        }
+
+        if !self.enabled {
+            return *geo;
+        }
+
+        let edge = u32::from(self.edge_threshold);
+        let window = u32::from(self.window_threshold);
+
+        let mut x_edges = vec![(screen.x, edge), (screen.x + screen.width, edge)];
+        let mut y_edges = vec![(screen.y, edge), (screen.y + screen.height, edge)];
+
+        for other in others {
+            if geo.y < other.y + other.height && other.y < geo.y + geo.height {
+                x_edges.push((other.x, window));
+                x_edges.push((other.x + other.width, window));
+            }
+
+            if geo.x < other.x + other.width && other.x < geo.x + geo.width {
+                y_edges.push((other.y, window));
+                y_edges.push((other.y + other.height, window));
+            }
+        }
+
+        Geometry {
+            x: snap_axis(geo.x, geo.width, &x_edges),
+            y: snap_axis(geo.y, geo.height, &y_edges),
+            width: geo.width,
+            height: geo.height,
+        }
     }
 }
 
@@ -165,10 +613,220 @@ pub enum Direction {
     SiblingCycleBackward,
 }
 
+/// A direction to rotate clients in, relative to their order in a tag tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    /// Move every client to the next position, and the last client to the first position.
+    Clockwise,
+    /// Move every client to the previous position, and the first client to the last position.
+    CounterClockwise,
+}
+
+/// An axis to reflect geometries along.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// Mirror horizontally, exchanging left and right.
+    Horizontal,
+    /// Mirror vertically, exchanging top and bottom.
+    Vertical,
+}
+
+/// Find the closest container in a given geometric direction.
+///
+/// Only containers whose geometry lies completely on the requested side of the geometry of
+/// `container` and overlaps with it on the other axis are considered. Non-geometric directions
+/// are not handled and yield `None`.
+pub fn find_geometric(sizes: &ClientSizes, container: ContainerId, dir: &Direction)
+    -> Option<ContainerId>
+{
+    fn overlap(a_start: u32, a_len: u32, b_start: u32, b_len: u32) -> bool {
+        a_start < b_start + b_len && b_start < a_start + a_len
+    }
+
+    let current = sizes.get(&container)?;
+
+    sizes
+        .iter()
+        .filter(|&(id, _)| *id != container)
+        .filter_map(|(id, geo)| {
+            let distance = match *dir {
+                Direction::Left if geo.x + geo.width <= current.x &&
+                    overlap(geo.y, geo.height, current.y, current.height) =>
+                    current.x - (geo.x + geo.width),
+                Direction::Right if current.x + current.width <= geo.x &&
+                    overlap(geo.y, geo.height, current.y, current.height) =>
+                    geo.x - (current.x + current.width),
+                Direction::Up if geo.y + geo.height <= current.y &&
+                    overlap(geo.x, geo.width, current.x, current.width) =>
+                    current.y - (geo.y + geo.height),
+                Direction::Down if current.y + current.height <= geo.y &&
+                    overlap(geo.x, geo.width, current.x, current.width) =>
+                    geo.y - (current.y + current.height),
+                _ => return None,
+            };
+
+            // prefer the closest container, and among those the top- or leftmost one
+            Some(((distance, geo.y, geo.x), *id))
+        })
+        .min()
+        .map(|(_, id)| id)
+}
+
+/// Check whether a tag tree only consists of client containers placed directly below the root.
+pub fn is_flat<C>(tagtree: &TagTree<C>) -> bool {
+    tagtree
+        .children(ContainerId::Root)
+        .all(|(_, c)| matches!(c, Container::Client(_)))
+}
+
+/// Get the client containers of a tag tree which take part in tiling, in preorder.
+pub fn tiled_clients<C>(tagtree: &TagTree<C>) -> Vec<ContainerId> {
+    tagtree
+        .preorder(ContainerId::Root)
+        .filter(|&(_, c)| matches!(c, Container::Client(_)) && !c.floating())
+        .map(|(id, _)| ContainerId::Index(id))
+        .collect()
+}
+
+/// Rotate all tiled clients of a tag tree by one position, and signify whether anything
+/// changed.
+///
+/// Positions are given by the order of clients in a preorder traversal of the tree.
+pub fn rotate_tiled_clients<C>(tagtree: &mut TagTree<C>, rotation: Rotation) -> bool {
+    let clients = tiled_clients(tagtree);
+
+    tagtree.rotate_clients(&clients, rotation)
+}
+
+/// Place all floating clients of a tag tree that have not been placed by a layout.
+///
+/// Clients are displayed at the geometry they were last floating at, or centered on the
+/// target geometry at half its size if they have never been floating before.
+pub fn place_floating<C>(tagtree: &TagTree<C>, target: &Geometry, sizes: &mut ClientSizes) {
+    for (id, c) in tagtree.preorder(ContainerId::Root) {
+        if !matches!(c, Container::Client(_)) || !c.floating() {
+            continue;
+        }
+
+        sizes
+            .entry(ContainerId::Index(id))
+            .or_insert_with(|| floating_placement(c, target));
+    }
+}
+
+/// Get the geometry a floating client is displayed at on the target geometry.
+///
+/// This is the geometry it was last floating at, or one centered on the target geometry at
+/// half its size if it has never been floating before.
+pub fn floating_placement<C>(container: &Container<C>, target: &Geometry) -> Geometry {
+    container.floating_geometry().unwrap_or_else(|| {
+        Geometry::centered_on(target, target.width / 2, target.height / 2)
+    })
+}
+
+/// Shrink the geometries of all tiled clients of a tag tree to leave room for their borders.
+pub fn apply_borders<C>(tagtree: &TagTree<C>, decorations: &Decorations, sizes: &mut ClientSizes) {
+    for (id, c) in tagtree.preorder(ContainerId::Root) {
+        if c.floating() {
+            continue;
+        }
+
+        if let Some(geo) = sizes.get_mut(&ContainerId::Index(id)) {
+            *geo = decorations.apply_border(geo);
+        }
+    }
+}
+
+/// Constrain the geometries of all tiled clients of a tag tree by their size hints.
+pub fn apply_size_hints<C>(tagtree: &TagTree<C>, sizes: &mut ClientSizes) {
+    for (id, c) in tagtree.preorder(ContainerId::Root) {
+        if let (Some(hints), false) = (c.size_hints(), c.floating()) {
+            if let Some(geo) = sizes.get_mut(&ContainerId::Index(id)) {
+                *geo = constrain_geometry(geo, hints);
+            }
+        }
+    }
+}
+
+/// Display all fullscreen clients of a tag tree on the entire target geometry.
+///
+/// This overrides any geometry computed by the layout, and ignores all gaps.
+pub fn place_fullscreen<C>(tagtree: &TagTree<C>, target: &Geometry, sizes: &mut ClientSizes) {
+    for (id, c) in tagtree.preorder(ContainerId::Root) {
+        if c.fullscreen() {
+            sizes.insert(ContainerId::Index(id), *target);
+        }
+    }
+}
+
+/// Find a neighbour of a client container in a tag tree rendered by the given layout.
+///
+/// Geometric directions are resolved by rendering the tree on the target geometry, so that
+/// the layout's placement of clients is taken into account. All other directions traverse the
+/// sequence of tiled clients, with sibling cycling wrapping around at the ends.
+pub fn find_neighbour<C>(layout: &dyn Layout<C>,
+                         tagtree: &TagTree<C>,
+                         target: &Geometry,
+                         container: ContainerId,
+                         dir: Direction) -> Option<ContainerId> {
+    let clients = tiled_clients(tagtree);
+    let index = clients.iter().position(|c| *c == container)?;
+    let len = clients.len();
+
+    match dir {
+        Direction::Left | Direction::Up | Direction::Right | Direction::Down => {
+            let mut sizes = ClientSizes::with_capacity(len);
+
+            layout.render(tagtree, target, &GapConfig::none(), &mut sizes);
+            find_geometric(&sizes, container, &dir)
+        },
+        Direction::InOrderForward | Direction::PreOrderForward =>
+            clients.get(index + 1).cloned(),
+        Direction::InOrderBackward | Direction::PreOrderBackward if index > 0 =>
+            clients.get(index - 1).cloned(),
+        Direction::InOrderBackward | Direction::PreOrderBackward => None,
+        Direction::SiblingCycleForward => Some(clients[(index + 1) % len]),
+        Direction::SiblingCycleBackward => Some(clients[(index + len - 1) % len]),
+    }
+}
+
+/// Insert a client into a flat tag tree after the cursor, or as the first client.
+pub fn insert_client_flat<C>(tagtree: &mut TagTree<C>, client: C) {
+    if let Some(cursor) = tagtree.get_cursor() {
+        tagtree.insert_client_after(cursor, client);
+    } else {
+        tagtree.insert_first_client(client);
+    }
+}
+
 /// A modification message sent to a layout.
 pub enum LayoutMessage {
     ParamAbs { id: usize, value: usize },
     ParamAdd { id: usize, inc: usize },
+    /// Adjust the inner gap of a tagset by the given amount of pixels.
+    ///
+    /// Handled by the tagset itself, and not passed on to the layout.
+    GapAdjust(i16),
+    /// Set both gaps of a tagset to the given amount of pixels, for instance to turn them off.
+    ///
+    /// Handled by the tagset itself, and not passed on to the layout.
+    GapSet(u16),
+    /// Adjust the outer gap of a tagset by the given amount of pixels.
+    ///
+    /// Handled by the tagset itself, and not passed on to the layout.
+    PaddingAdjust(i16),
+    /// Rotate the clients of a tagset by one position.
+    ///
+    /// Handled by the tagset, which passes its tag tree to the layout.
+    Rotate(Rotation),
+    /// Focus the tiled client at the given index, for instance to select a tab.
+    ///
+    /// Handled by the tagset itself, and not passed on to the layout.
+    FocusIndex(usize),
+    /// Toggle the reflection of a reflected layout along the given axis.
+    ///
+    /// Ignored by all other layouts.
+    Reflect(Axis),
 }
 
 /// A map holding clients' geometries as constructed by a layout.
@@ -183,18 +841,26 @@ pub type ClientSizes = HashMap<ContainerId, Geometry>;
 /// a container upon removal. All fields tracking focus and selection are not maintained by
 /// the layout.
 pub trait Layout<C> : Debug {
+    /// Get a short, human-readable name of the layout, for instance for status displays.
+    fn name(&self) -> &'static str;
+
     /// Compute geometries of the given tag tree on a given geometry.
     ///
     /// The tag tree can be assumed to be consistent with the layout. The layout can either
     /// ignore floating containers completely, or provide geometries for them that are then
     /// used in the actual rendering process. In either case, the floating windows and/or
     /// containers are then drawn at the provided or generated locations beginning at the root.
-    fn render(&self, &TagTree<C>, &Geometry, &mut ClientSizes);
+    /// Floating clients without a geometry are placed by the tagset, see `place_floating`.
+    /// The geometries of tiled clients need to honor the given gaps.
+    fn render(&self, &TagTree<C>, &Geometry, &GapConfig, &mut ClientSizes);
 
     /// Check whether the tag tree is consistent with the layout.
     fn check_tree(&self, &TagTree<C>) -> bool;
 
     /// Transform a tag tree to be consistent with the layout.
+    ///
+    /// The relative order of clients in the tree (as given by a preorder traversal) needs to
+    /// be preserved, so that clients keep their positions when switching layouts.
     fn fixup_tree(&self, &mut TagTree<C>);
 
     /// Insert a new client into the tree and signify whether a new render is necessary.
@@ -216,12 +882,46 @@ pub trait Layout<C> : Debug {
     /// the tree are allowed to fit the layout. However, the container must be removed.
     fn delete_container(&self, &mut TagTree<C>, ContainerId) -> bool;
 
-    /// Find an appropriate neighbour for a container located in the given direction.
+    /// Find an appropriate neighbour for a container located in the given direction, with the
+    /// tag tree rendered on the given geometry.
     ///
     /// This is used to compute focus transitions and tree swap operations. In some cases,
     /// this can leave the tree in a state not consistent with the layout, which is then
     /// fixed using `fixup_tree`.
-    fn find_container(&self, &TagTree<C>, ContainerId, Direction) -> Option<ContainerId>;
+    fn find_container(&self, &TagTree<C>, &Geometry, ContainerId, Direction)
+        -> Option<ContainerId>;
+
+    /// Find the client container displayed at the given point, if any.
+    ///
+    /// The default implementation renders the tag tree on the given geometry and looks up the
+    /// point in the result, with fullscreen and floating clients taking precedence over tiled
+    /// ones. Layouts can override it if they can locate clients more efficiently.
+    fn container_at_point(&self,
+                          tagtree: &TagTree<C>,
+                          target: &Geometry,
+                          gaps: &GapConfig,
+                          x: u32,
+                          y: u32) -> Option<ContainerId> {
+        let mut sizes = ClientSizes::new();
+        self.render(tagtree, target, gaps, &mut sizes);
+        apply_size_hints(tagtree, &mut sizes);
+        place_floating(tagtree, target, &mut sizes);
+        place_fullscreen(tagtree, target, &mut sizes);
+
+        let mut clients: Vec<_> = tagtree
+            .preorder(ContainerId::Root)
+            .filter(|&(_, c)| matches!(c, Container::Client(_)))
+            .collect();
+
+        // fullscreen clients are drawn on top of floating ones, which are drawn on top of
+        // tiled ones
+        clients.sort_by_key(|&(_, c)| (!c.fullscreen(), !c.floating()));
+
+        clients
+            .into_iter()
+            .map(|(id, _)| ContainerId::Index(id))
+            .find(|id| sizes.get(id).is_some_and(|geo| geo.contains_point(x, y)))
+    }
 
     /// Swap two containers in the tree, and signify whether a new render is necessary.
     ///
@@ -235,8 +935,31 @@ pub trait Layout<C> : Debug {
     /// updates on the tree.
     fn move_container(&self, &mut TagTree<C>, ContainerId, ContainerId) -> bool;
 
+    /// Rotate the clients in the tree by one position, and signify whether a new render is
+    /// necessary.
+    ///
+    /// The default implementation does nothing. Layouts placing clients in the order of the
+    /// tree can use `rotate_tiled_clients`.
+    fn rotate(&self, _tagtree: &mut TagTree<C>, _rotation: Rotation) -> bool {
+        false
+    }
+
     /// Process a modification message and signify whether a new render is necessary.
     fn process_msg(&mut self, LayoutMessage) -> bool;
+
+    /// Get the number of tiled clients the layout works best with, if any.
+    ///
+    /// This is only a hint, the layout still needs to handle any number of clients.
+    fn preferred_window_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// Wrap the layout to mirror the geometries it computes along the given axis.
+    fn reflect(self, axis: Axis) -> Reflected<Self>
+        where Self: Sized
+    {
+        Reflected::new(self, axis)
+    }
 }
 
 /// The manual layout.
@@ -247,7 +970,13 @@ pub trait Layout<C> : Debug {
 pub struct Manual { }
 
 impl<C> Layout<C> for Manual {
-    fn render(&self, tagtree: &TagTree<C>, target: &Geometry, sizes: &mut ClientSizes) {
+    fn name(&self) -> &'static str { "manual" }
+
+    fn render(&self,
+              tagtree: &TagTree<C>,
+              target: &Geometry,
+              gaps: &GapConfig,
+              sizes: &mut ClientSizes) {
         fn handle_split<C>(tagtree: &TagTree<C>,
                            geo_cache: &mut HashMap<ContainerId, (Geometry, bool)>,
                            current_id: ContainerId,
@@ -285,6 +1014,7 @@ impl<C> Layout<C> for Manual {
         // the geometry cache contains a geometry and a "will be actually rendered" flag.
         // this is needed to compute the geometries of hidden containers in tabbed splits
         // that are visible because they are floating
+        let target = &gaps.apply_outer(target);
         let mut geo_cache = HashMap::with_capacity(tagtree.len());
         geo_cache.insert(ContainerId::Root, (*target, true));
 
@@ -299,9 +1029,15 @@ impl<C> Layout<C> for Manual {
         for (current_id, current) in tagtree.preorder(ContainerId::Root) {
             let current_id = ContainerId::Index(current_id);
 
-            // just move floating containers to the middle of the screen
+            // move floating containers to their last position or the middle of the screen
             if current.floating() {
-                geo_cache.get_mut(&current_id).unwrap().0.center(target);
+                let cached = &mut geo_cache.get_mut(&current_id).unwrap().0;
+
+                if let Some(geo) = current.floating_geometry() {
+                    *cached = geo;
+                } else {
+                    cached.center(target);
+                }
             }
 
             // since we are iterating over the preorder traversal of the tree, we can
@@ -316,7 +1052,8 @@ impl<C> Layout<C> for Manual {
                                  s.get_last_focused());
                 },
                 Container::Client(c) => if geo_cache[&current_id].1 {
-                    sizes.insert(current_id, geo_cache[&current_id].0);
+                    let geo = gaps.apply_inner(&geo_cache[&current_id].0, target);
+                    sizes.insert(current_id, geo);
                 },
             }
         }
@@ -351,9 +1088,11 @@ impl<C> Layout<C> for Manual {
         true
     }
 
-    fn find_container(&self, tagtree: &TagTree<C>, container: ContainerId, dir: Direction)
-        -> Option<ContainerId>
-    {
+    fn find_container(&self,
+                      tagtree: &TagTree<C>,
+                      target: &Geometry,
+                      container: ContainerId,
+                      dir: Direction) -> Option<ContainerId> {
         // TODO
         None
     }
@@ -362,8 +1101,7 @@ impl<C> Layout<C> for Manual {
                        tagtree: &mut TagTree<C>,
                        a: ContainerId,
                        b: ContainerId) -> bool {
-        // TODO
-        false
+        tagtree.swap_clients(a, b)
     }
 
     fn move_container(&self,
@@ -376,3 +1114,210 @@ impl<C> Layout<C> for Manual {
 
     fn process_msg(&mut self, _: LayoutMessage) -> bool { false }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Geometries of various sizes and positions to check properties on.
+    fn samples() -> Vec<Geometry> {
+        vec![
+            Geometry::new(0, 0, 1, 1),
+            Geometry::new(0, 0, 7, 3),
+            Geometry::new(13, 29, 100, 61),
+            Geometry::new(1920, 0, 1279, 1023),
+        ]
+    }
+
+    #[test]
+    fn split_halves_have_no_gap_and_no_overlap() {
+        for geo in samples() {
+            for &ratio in &[0, 1, 33, 50, 99, 100] {
+                let (left, right) = geo.split_horizontal(SplitRatio::new(ratio));
+                assert_eq!((left.x, left.y, left.height), (geo.x, geo.y, geo.height));
+                assert_eq!((right.y, right.height), (geo.y, geo.height));
+                assert_eq!(left.x + left.width, right.x);
+                assert_eq!(right.x + right.width, geo.x + geo.width);
+                assert!(!left.intersects(&right));
+
+                let (top, bot) = geo.split_vertical(SplitRatio::new(ratio));
+                assert_eq!((top.x, top.y, top.width), (geo.x, geo.y, geo.width));
+                assert_eq!((bot.x, bot.width), (geo.x, geo.width));
+                assert_eq!(top.y + top.height, bot.y);
+                assert_eq!(bot.y + bot.height, geo.y + geo.height);
+                assert!(!top.intersects(&bot));
+            }
+        }
+    }
+
+    #[test]
+    fn split_n_parts_have_no_gap_and_no_overlap() {
+        for geo in samples() {
+            for n in 1..=5 {
+                let columns = geo.split_horizontal_n(n);
+                assert_eq!(columns.len(), n);
+                assert_eq!(columns[0].x, geo.x);
+                assert_eq!(columns[n - 1].x + columns[n - 1].width, geo.x + geo.width);
+
+                for pair in columns.windows(2) {
+                    assert_eq!(pair[0].x + pair[0].width, pair[1].x);
+                    assert!(!pair[0].intersects(&pair[1]));
+                }
+
+                let rows = geo.split_vertical_n(n);
+                assert_eq!(rows.len(), n);
+                assert_eq!(rows[0].y, geo.y);
+                assert_eq!(rows[n - 1].y + rows[n - 1].height, geo.y + geo.height);
+
+                for pair in rows.windows(2) {
+                    assert_eq!(pair[0].y + pair[0].height, pair[1].y);
+                    assert!(!pair[0].intersects(&pair[1]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn intersects_excludes_touching_edges() {
+        let geo = Geometry::new(10, 10, 20, 20);
+
+        assert!(geo.intersects(&geo));
+        assert!(geo.intersects(&Geometry::new(29, 29, 5, 5)));
+        assert!(geo.intersects(&Geometry::new(0, 0, 11, 11)));
+        assert!(!geo.intersects(&Geometry::new(30, 10, 5, 5)));
+        assert!(!geo.intersects(&Geometry::new(10, 30, 5, 5)));
+        assert!(!geo.intersects(&Geometry::new(0, 0, 10, 40)));
+        assert!(!geo.intersects(&Geometry::new(0, 0, 40, 10)));
+    }
+
+    #[test]
+    fn contains_point_is_half_open() {
+        let geo = Geometry::new(10, 20, 30, 40);
+
+        assert!(geo.contains_point(10, 20));
+        assert!(geo.contains_point(39, 59));
+        assert!(!geo.contains_point(9, 20));
+        assert!(!geo.contains_point(10, 19));
+        assert!(!geo.contains_point(40, 20));
+        assert!(!geo.contains_point(10, 60));
+    }
+
+    #[test]
+    fn translated_clamps_to_positive_quadrant() {
+        let geo = Geometry::new(10, 20, 30, 40);
+
+        assert_eq!(geo.translated(5, -5), Geometry::new(15, 15, 30, 40));
+        assert_eq!(geo.translated(-15, -25), Geometry::new(0, 0, 30, 40));
+        assert_eq!(geo.translated(0, 0), geo);
+    }
+
+    #[test]
+    fn reflected_stays_within_reference() {
+        let reference = Geometry::new(100, 50, 800, 600);
+        let inside = Geometry::new(100, 50, 200, 100);
+        let outside = Geometry::new(2000, 1000, 200, 100);
+
+        assert_eq!(inside.reflected(&reference, Axis::Horizontal),
+                   Geometry::new(700, 50, 200, 100));
+        assert_eq!(inside.reflected(&reference, Axis::Vertical),
+                   Geometry::new(100, 550, 200, 100));
+        assert_eq!(outside.reflected(&reference, Axis::Horizontal),
+                   Geometry::new(0, 1000, 200, 100));
+        assert_eq!(outside.reflected(&reference, Axis::Vertical),
+                   Geometry::new(2000, 0, 200, 100));
+    }
+
+    #[test]
+    fn container_at_point_resolves_corners_and_boundaries() {
+        use layout::grid::Grid;
+        use tree::SplitType;
+
+        let layout = Grid::new(false);
+        let target = Geometry::new(0, 0, 101, 51);
+        let mut tree = TagTree::new(SplitType::Horizontal(SplitRatio::new(50)));
+
+        for client in 0..4u32 {
+            layout.insert_client(&mut tree, client);
+        }
+
+        let mut sizes = ClientSizes::new();
+        layout.render(&tree, &target, &GapConfig::none(), &mut sizes);
+        assert_eq!(sizes.len(), 4);
+
+        let at = |x, y| layout.container_at_point(&tree, &target, &GapConfig::none(), x, y);
+
+        for (&id, geo) in &sizes {
+            let (right, bottom) = (geo.x + geo.width - 1, geo.y + geo.height - 1);
+
+            // all four corner pixels belong to the client
+            for &(x, y) in &[(geo.x, geo.y), (right, geo.y), (geo.x, bottom), (right, bottom)] {
+                assert_eq!(at(x, y), Some(id), "corner {},{} of {:?}", x, y, geo);
+            }
+
+            // the pixels just past the right and bottom edges belong to a neighbour, if any
+            for &(x, y) in &[(right + 1, geo.y), (geo.x, bottom + 1)] {
+                let neighbour = sizes.iter().find(|&(_, g)| g.contains_point(x, y));
+                assert_eq!(at(x, y), neighbour.map(|(&n, _)| n));
+                assert_ne!(at(x, y), Some(id));
+            }
+        }
+
+        assert_eq!(at(101, 0), None);
+        assert_eq!(at(0, 51), None);
+    }
+
+    #[test]
+    fn centered_on_centers_and_clamps() {
+        let screen = Geometry::new(0, 0, 800, 600);
+
+        assert_eq!(Geometry::centered_on(&screen, 200, 100), Geometry::new(300, 250, 200, 100));
+        assert_eq!(Geometry::centered_on(&Geometry::new(800, 0, 800, 600), 200, 100),
+                   Geometry::new(1100, 250, 200, 100));
+        assert_eq!(Geometry::centered_on(&screen, 1000, 100), Geometry::new(0, 250, 800, 100));
+    }
+
+    #[test]
+    fn snap_aligns_with_screen_edges() {
+        let snap = SnapConfig::default();
+        let screen = Geometry::new(0, 0, 800, 600);
+
+        assert_eq!(snap.snap(&Geometry::new(5, 3, 100, 100), &screen, &[]),
+                   Geometry::new(0, 0, 100, 100));
+        assert_eq!(snap.snap(&Geometry::new(697, 503, 100, 100), &screen, &[]),
+                   Geometry::new(700, 500, 100, 100));
+        assert_eq!(snap.snap(&Geometry::new(300, 200, 100, 100), &screen, &[]),
+                   Geometry::new(300, 200, 100, 100));
+    }
+
+    #[test]
+    fn snap_aligns_with_overlapping_windows() {
+        let snap = SnapConfig::default();
+        let screen = Geometry::new(0, 0, 800, 600);
+        let others = [Geometry::new(200, 200, 100, 100)];
+
+        // the left edge snaps to the other window's right edge, and vice versa
+        assert_eq!(snap.snap(&Geometry::new(305, 210, 50, 50), &screen, &others),
+                   Geometry::new(300, 210, 50, 50));
+        assert_eq!(snap.snap(&Geometry::new(150, 210, 46, 50), &screen, &others),
+                   Geometry::new(154, 210, 46, 50));
+
+        // windows not overlapping on the other axis are ignored
+        assert_eq!(snap.snap(&Geometry::new(305, 400, 50, 50), &screen, &others),
+                   Geometry::new(305, 400, 50, 50));
+    }
+
+    #[test]
+    fn snap_threshold_is_inclusive() {
+        let mut snap = SnapConfig::default();
+        let screen = Geometry::new(0, 0, 800, 600);
+
+        assert_eq!(snap.snap(&Geometry::new(8, 300, 100, 100), &screen, &[]),
+                   Geometry::new(0, 300, 100, 100));
+        assert_eq!(snap.snap(&Geometry::new(9, 300, 100, 100), &screen, &[]),
+                   Geometry::new(9, 300, 100, 100));
+
+        snap.enabled = false;
+        assert_eq!(snap.snap(&Geometry::new(8, 300, 100, 100), &screen, &[]),
+                   Geometry::new(8, 300, 100, 100));
+    }
+}
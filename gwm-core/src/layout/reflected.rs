@@ -0,0 +1,164 @@
+use layout::*;
+use tree::{ContainerId, TagTree};
+
+/// A wrapper mirroring the geometries computed by another layout.
+///
+/// Tiled clients are arranged by the wrapped layout, and then reflected within the area
+/// rendered on along one or both axes. This allows, for instance, to put the master client of
+/// a layout on the right instead of the left. Directions passed to the wrapped layout are
+/// mirrored accordingly.
+#[derive(Debug)]
+pub struct Reflected<L> {
+    /// The wrapped layout.
+    inner: L,
+    /// Whether the layout is mirrored horizontally.
+    horizontal: bool,
+    /// Whether the layout is mirrored vertically.
+    vertical: bool,
+}
+
+impl<L> Reflected<L> {
+    /// Construct a new layout mirroring the given one along the given axis.
+    pub fn new(inner: L, axis: Axis) -> Reflected<L> {
+        Reflected {
+            inner,
+            horizontal: axis == Axis::Horizontal,
+            vertical: axis == Axis::Vertical,
+        }
+    }
+
+    /// Get whether the layout is mirrored horizontally and vertically, in that order.
+    pub fn axes(&self) -> (bool, bool) {
+        (self.horizontal, self.vertical)
+    }
+
+    /// Mirror a direction along the axes the layout is reflected on.
+    fn reflect_direction(&self, dir: Direction) -> Direction {
+        match dir {
+            Direction::Left if self.horizontal => Direction::Right,
+            Direction::Right if self.horizontal => Direction::Left,
+            Direction::Up if self.vertical => Direction::Down,
+            Direction::Down if self.vertical => Direction::Up,
+            dir => dir,
+        }
+    }
+}
+
+impl<C, L: Layout<C>> Layout<C> for Reflected<L> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn render(&self,
+              tagtree: &TagTree<C>,
+              target: &Geometry,
+              gaps: &GapConfig,
+              sizes: &mut ClientSizes) {
+        let mut inner_sizes = ClientSizes::new();
+        self.inner.render(tagtree, target, gaps, &mut inner_sizes);
+
+        let tiled = tiled_clients(tagtree);
+
+        for (id, mut geo) in inner_sizes {
+            if !tiled.contains(&id) {
+                sizes.insert(id, geo);
+                continue;
+            }
+
+            if self.horizontal {
+                geo = geo.reflected(target, Axis::Horizontal);
+            }
+
+            if self.vertical {
+                geo = geo.reflected(target, Axis::Vertical);
+            }
+
+            sizes.insert(id, geo);
+        }
+    }
+
+    fn check_tree(&self, tagtree: &TagTree<C>) -> bool {
+        self.inner.check_tree(tagtree)
+    }
+
+    fn fixup_tree(&self, tagtree: &mut TagTree<C>) {
+        self.inner.fixup_tree(tagtree)
+    }
+
+    fn insert_client(&self, tagtree: &mut TagTree<C>, client: C) -> bool {
+        self.inner.insert_client(tagtree, client)
+    }
+
+    fn insert_container(&self, tagtree: &mut TagTree<C>, src: &TagTree<C>, root: ContainerId)
+        -> bool
+    {
+        self.inner.insert_container(tagtree, src, root)
+    }
+
+    fn delete_container(&self, tagtree: &mut TagTree<C>, container: ContainerId) -> bool {
+        self.inner.delete_container(tagtree, container)
+    }
+
+    fn find_container(&self,
+                      tagtree: &TagTree<C>,
+                      target: &Geometry,
+                      container: ContainerId,
+                      dir: Direction) -> Option<ContainerId> {
+        self.inner.find_container(tagtree, target, container, self.reflect_direction(dir))
+    }
+
+    fn swap_containers(&self,
+                       tagtree: &mut TagTree<C>,
+                       a: ContainerId,
+                       b: ContainerId) -> bool {
+        self.inner.swap_containers(tagtree, a, b)
+    }
+
+    fn move_container(&self,
+                      tagtree: &mut TagTree<C>,
+                      cursor: ContainerId,
+                      target: ContainerId) -> bool {
+        self.inner.move_container(tagtree, cursor, target)
+    }
+
+    fn rotate(&self, tagtree: &mut TagTree<C>, rotation: Rotation) -> bool {
+        self.inner.rotate(tagtree, rotation)
+    }
+
+    fn preferred_window_count(&self) -> Option<usize> {
+        self.inner.preferred_window_count()
+    }
+
+    fn process_msg(&mut self, msg: LayoutMessage) -> bool {
+        match msg {
+            LayoutMessage::Reflect(Axis::Horizontal) => {
+                self.horizontal = !self.horizontal;
+                true
+            },
+            LayoutMessage::Reflect(Axis::Vertical) => {
+                self.vertical = !self.vertical;
+                true
+            },
+            msg => self.inner.process_msg(msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layout::grid::Grid;
+
+    #[test]
+    fn name_is_inner_name_and_axes_toggle() {
+        let mut layout = Reflected::new(Grid::new(false), Axis::Horizontal);
+        assert_eq!(Layout::<u32>::name(&layout), Layout::<u32>::name(&Grid::new(false)));
+        assert_eq!(layout.axes(), (true, false));
+
+        assert!(Layout::<u32>::process_msg(&mut layout, LayoutMessage::Reflect(Axis::Vertical)));
+        assert!(Layout::<u32>::process_msg(&mut layout,
+                                            LayoutMessage::Reflect(Axis::Horizontal)));
+        assert_eq!(layout.axes(), (false, true));
+        assert_eq!(Layout::<u32>::name(&layout), Layout::<u32>::name(&Grid::new(false)));
+    }
+}
@@ -0,0 +1,146 @@
+use std::cmp::min;
+
+use layout::*;
+use tree::{ContainerId, TagTree};
+
+/// The tabbed layout.
+///
+/// All clients are direct children of the root and share the same geometry, which leaves
+/// room for a tab bar at the top of the area rendered on. Drawing the tab bar is left to the
+/// window manager. Moving left and right cycles through the tabs, and a tab can be selected
+/// by its index using `LayoutMessage::FocusIndex`.
+#[derive(Debug)]
+pub struct Tabbed {
+    /// The height of the tab bar in pixels.
+    bar_height: u32,
+}
+
+/// The maximum height of the tab bar in pixels.
+const MAX_BAR_HEIGHT: u32 = 256;
+
+impl Tabbed {
+    /// Construct a new tabbed layout leaving room for a tab bar of the given height.
+    ///
+    /// The height is limited to `MAX_BAR_HEIGHT`.
+    pub fn new(bar_height: u32) -> Tabbed {
+        Tabbed { bar_height: min(bar_height, MAX_BAR_HEIGHT) }
+    }
+
+    /// Get the geometry of the tab bar when rendering on the given geometry.
+    pub fn bar_geometry(&self, target: &Geometry, gaps: &GapConfig) -> Geometry {
+        let target = gaps.apply_outer(target);
+
+        Geometry {
+            height: min(self.bar_height, target.height),
+            ..target
+        }
+    }
+}
+
+impl Default for Tabbed {
+    fn default() -> Tabbed {
+        Tabbed::new(20)
+    }
+}
+
+impl<C> Layout<C> for Tabbed {
+    fn name(&self) -> &'static str { "tabbed" }
+
+    fn render(&self,
+              tagtree: &TagTree<C>,
+              target: &Geometry,
+              gaps: &GapConfig,
+              sizes: &mut ClientSizes) {
+        let target = gaps.apply_outer(target);
+        let bar_height = min(self.bar_height, target.height);
+        let geo = Geometry {
+            y: target.y + bar_height,
+            height: target.height - bar_height,
+            ..target
+        };
+
+        for client in tiled_clients(tagtree) {
+            sizes.insert(client, geo);
+        }
+    }
+
+    fn check_tree(&self, tagtree: &TagTree<C>) -> bool {
+        is_flat(tagtree)
+    }
+
+    fn fixup_tree(&self, tagtree: &mut TagTree<C>) {
+        tagtree.flatten();
+    }
+
+    fn insert_client(&self, tagtree: &mut TagTree<C>, client: C) -> bool {
+        insert_client_flat(tagtree, client);
+
+        true
+    }
+
+    fn insert_container(&self, tagtree: &mut TagTree<C>, src: &TagTree<C>, root: ContainerId)
+        -> bool
+    {
+        // TODO
+        false
+    }
+
+    fn delete_container(&self, tagtree: &mut TagTree<C>, container: ContainerId) -> bool {
+        tagtree.delete_container(container);
+
+        true
+    }
+
+    fn find_container(&self,
+                      tagtree: &TagTree<C>,
+                      target: &Geometry,
+                      container: ContainerId,
+                      dir: Direction) -> Option<ContainerId> {
+        let dir = match dir {
+            Direction::Left => Direction::SiblingCycleBackward,
+            Direction::Right => Direction::SiblingCycleForward,
+            Direction::Up | Direction::Down => return None,
+            dir => dir,
+        };
+
+        find_neighbour(self, tagtree, target, container, dir)
+    }
+
+    fn swap_containers(&self,
+                       tagtree: &mut TagTree<C>,
+                       a: ContainerId,
+                       b: ContainerId) -> bool {
+        tagtree.swap_clients(a, b)
+    }
+
+    fn move_container(&self,
+                      tagtree: &mut TagTree<C>,
+                      cursor: ContainerId,
+                      target: ContainerId) -> bool {
+        // TODO
+        false
+    }
+
+    fn rotate(&self, tagtree: &mut TagTree<C>, rotation: Rotation) -> bool {
+        rotate_tiled_clients(tagtree, rotation)
+    }
+
+    fn process_msg(&mut self, msg: LayoutMessage) -> bool {
+        match msg {
+            LayoutMessage::ParamAbs { id: 0, value } => {
+                self.bar_height = min(value, MAX_BAR_HEIGHT as usize) as u32;
+                true
+            },
+            LayoutMessage::ParamAdd { id: 0, inc } => {
+                let value = (self.bar_height as usize).saturating_add(inc);
+                self.bar_height = min(value, MAX_BAR_HEIGHT as usize) as u32;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn preferred_window_count(&self) -> Option<usize> {
+        Some(1)
+    }
+}
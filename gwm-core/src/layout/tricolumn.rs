@@ -0,0 +1,178 @@
+use std::cmp::min;
+
+use layout::*;
+use tree::{ContainerId, SplitRatio, TagTree};
+
+/// The three column layout.
+///
+/// All clients are direct children of the root. The first client is placed in the middle
+/// column, while the first half of the remaining clients is stacked in the left column, and
+/// the rest in the right column. The widths of the side columns are controlled independently,
+/// and empty side columns leave their space to the middle column.
+#[derive(Debug)]
+pub struct TriColumn {
+    /// The percentage of the screen width taken up by the left column (parameter 0).
+    left_factor: u8,
+    /// The percentage of the screen width taken up by the right column (parameter 1).
+    right_factor: u8,
+}
+
+impl TriColumn {
+    /// Construct a new three column layout using the given side column widths in percent.
+    pub fn new(left_factor: u8, right_factor: u8) -> TriColumn {
+        let left_factor = min(left_factor, 100);
+        let right_factor = min(right_factor, 100 - left_factor);
+
+        TriColumn { left_factor, right_factor }
+    }
+
+    /// Set one of the side column widths, keeping the sum of both widths within bounds.
+    fn set_factor(&mut self, id: usize, value: usize) -> bool {
+        match id {
+            0 => self.left_factor = min(value, 100 - self.right_factor as usize) as u8,
+            1 => self.right_factor = min(value, 100 - self.left_factor as usize) as u8,
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+impl<C> Layout<C> for TriColumn {
+    fn name(&self) -> &'static str { "tricolumn" }
+
+    fn render(&self,
+              tagtree: &TagTree<C>,
+              target: &Geometry,
+              gaps: &GapConfig,
+              sizes: &mut ClientSizes) {
+        let target = &gaps.apply_outer(target);
+        let clients = tiled_clients(tagtree);
+
+        if clients.is_empty() {
+            return;
+        }
+
+        let num_slaves = clients.len() - 1;
+        let num_left = num_slaves.div_ceil(2);
+        let num_right = num_slaves - num_left;
+
+        let left_width = if num_left > 0 {
+            target.width * SplitRatio::new(self.left_factor)
+        } else {
+            0
+        };
+
+        let right_width = if num_right > 0 {
+            target.width * SplitRatio::new(self.right_factor)
+        } else {
+            0
+        };
+
+        let master_width = target.width - left_width - right_width;
+
+        let left = Geometry::new(target.x, target.y, left_width, target.height);
+        let master = Geometry::new(target.x + left_width, target.y, master_width, target.height);
+        let right = Geometry::new(target.x + left_width + master_width, target.y,
+                                  right_width, target.height);
+
+        sizes.insert(clients[0], gaps.apply_inner(&master, target));
+
+        let (left_clients, right_clients) = clients[1..].split_at(num_left);
+
+        for (client, geo) in left_clients.iter().zip(left.split_vertical_n(num_left)) {
+            sizes.insert(*client, gaps.apply_inner(&geo, target));
+        }
+
+        for (client, geo) in right_clients.iter().zip(right.split_vertical_n(num_right)) {
+            sizes.insert(*client, gaps.apply_inner(&geo, target));
+        }
+    }
+
+    fn check_tree(&self, tagtree: &TagTree<C>) -> bool {
+        is_flat(tagtree)
+    }
+
+    fn fixup_tree(&self, tagtree: &mut TagTree<C>) {
+        tagtree.flatten();
+    }
+
+    fn insert_client(&self, tagtree: &mut TagTree<C>, client: C) -> bool {
+        insert_client_flat(tagtree, client);
+
+        true
+    }
+
+    fn insert_container(&self, tagtree: &mut TagTree<C>, src: &TagTree<C>, root: ContainerId)
+        -> bool
+    {
+        // TODO
+        false
+    }
+
+    fn delete_container(&self, tagtree: &mut TagTree<C>, container: ContainerId) -> bool {
+        tagtree.delete_container(container);
+
+        true
+    }
+
+    fn find_container(&self,
+                      tagtree: &TagTree<C>,
+                      target: &Geometry,
+                      container: ContainerId,
+                      dir: Direction) -> Option<ContainerId> {
+        find_neighbour(self, tagtree, target, container, dir)
+    }
+
+    fn swap_containers(&self,
+                       tagtree: &mut TagTree<C>,
+                       a: ContainerId,
+                       b: ContainerId) -> bool {
+        tagtree.swap_clients(a, b)
+    }
+
+    fn move_container(&self,
+                      tagtree: &mut TagTree<C>,
+                      cursor: ContainerId,
+                      target: ContainerId) -> bool {
+        // TODO
+        false
+    }
+
+    fn rotate(&self, tagtree: &mut TagTree<C>, rotation: Rotation) -> bool {
+        rotate_tiled_clients(tagtree, rotation)
+    }
+
+    fn process_msg(&mut self, msg: LayoutMessage) -> bool {
+        match msg {
+            LayoutMessage::ParamAbs { id, value } => self.set_factor(id, value),
+            LayoutMessage::ParamAdd { id: 0, inc } => {
+                let value = self.left_factor as usize + min(inc, 100);
+                self.set_factor(0, value)
+            },
+            LayoutMessage::ParamAdd { id: 1, inc } => {
+                let value = self.right_factor as usize + min(inc, 100);
+                self.set_factor(1, value)
+            },
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_increments_keep_factors_in_bounds() {
+        let mut layout = TriColumn::new(20, 30);
+
+        assert!(Layout::<u32>::process_msg(&mut layout,
+                                            LayoutMessage::ParamAdd { id: 0, inc: usize::MAX }));
+        assert_eq!((layout.left_factor, layout.right_factor), (70, 30));
+
+        assert!(Layout::<u32>::process_msg(&mut layout,
+                                            LayoutMessage::ParamAdd { id: 1, inc: usize::MAX }));
+        assert_eq!((layout.left_factor, layout.right_factor), (70, 30));
+    }
+}
@@ -1,6 +1,11 @@
 #![feature(type_alias_enum_variants)]
 extern crate generational_arena;
+#[macro_use]
+extern crate log;
+extern crate regex;
 
 pub mod config;
+pub mod focus;
 pub mod layout;
+pub mod rules;
 pub mod tree;
@@ -0,0 +1,130 @@
+use regex::Regex;
+
+use config::Tag;
+use tree::ClientProperties;
+
+/// An action to perform on a newly managed client matched by a rule.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RuleAction {
+    /// Tag the client with the given tag.
+    AssignTag(Tag),
+    /// Make the client floating.
+    SetFloat,
+    /// Display the client fullscreen.
+    SetFullscreen,
+    /// Do not manage the client at all.
+    Ignore,
+    /// Make the client floating and center it on the screen.
+    Center,
+}
+
+/// A rule matching clients on their class, instance and title.
+///
+/// Absent patterns match every client, so that a rule without any patterns matches all
+/// clients.
+#[derive(Debug)]
+pub struct Rule {
+    /// The pattern to match the class part of a client's `WM_CLASS` property against.
+    pub class_pattern: Option<Regex>,
+    /// The pattern to match the instance part of a client's `WM_CLASS` property against.
+    pub instance_pattern: Option<Regex>,
+    /// The pattern to match a client's `WM_NAME` property against.
+    pub title_pattern: Option<Regex>,
+    /// The action to perform on matching clients.
+    pub action: RuleAction,
+}
+
+impl Rule {
+    /// Construct a rule by compiling the given patterns.
+    pub fn new(class: Option<&str>,
+               instance: Option<&str>,
+               title: Option<&str>,
+               action: RuleAction) -> Result<Rule, regex::Error> {
+        let compile = |p: Option<&str>| p.map(Regex::new).transpose();
+
+        Ok(Rule {
+            class_pattern: compile(class)?,
+            instance_pattern: compile(instance)?,
+            title_pattern: compile(title)?,
+            action,
+        })
+    }
+
+    /// Check whether a client with the given properties is matched by the rule.
+    pub fn matches(&self, properties: &ClientProperties) -> bool {
+        let check = |p: &Option<Regex>, s| p.as_ref().is_none_or(|p| p.is_match(s));
+
+        check(&self.class_pattern, &properties.class) &&
+            check(&self.instance_pattern, &properties.instance) &&
+            check(&self.title_pattern, &properties.title)
+    }
+}
+
+/// Find the action of the first rule matching a client with the given properties, if any.
+pub fn match_rules<'a>(rules: &'a [Rule], properties: &ClientProperties)
+    -> Option<&'a RuleAction>
+{
+    rules
+        .iter()
+        .find(|r| r.matches(properties))
+        .map(|r| &r.action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Construct the properties of a client with the given class, instance and title.
+    fn client(class: &str, instance: &str, title: &str) -> ClientProperties {
+        ClientProperties {
+            class: class.to_owned(),
+            instance: instance.to_owned(),
+            title: title.to_owned(),
+            ..ClientProperties::default()
+        }
+    }
+
+    #[test]
+    fn absent_patterns_match_everything() {
+        let rule = Rule::new(None, None, None, RuleAction::SetFloat).unwrap();
+        assert!(rule.matches(&client("Firefox", "Navigator", "Mozilla Firefox")));
+        assert!(rule.matches(&client("", "", "")));
+
+        let rule = Rule::new(Some("^mpv$"), None, None, RuleAction::SetFloat).unwrap();
+        assert!(rule.matches(&client("mpv", "gl", "video.mkv")));
+        assert!(!rule.matches(&client("Mpv", "gl", "video.mkv")));
+    }
+
+    #[test]
+    fn all_present_patterns_need_to_match() {
+        let rule = Rule::new(Some("^Gimp"), None, Some("Toolbox"), RuleAction::SetFloat).unwrap();
+
+        assert!(rule.matches(&client("Gimp-2.10", "gimp", "Toolbox - Tool Options")));
+        assert!(!rule.matches(&client("Gimp-2.10", "gimp", "Untitled")));
+        assert!(!rule.matches(&client("Inkscape", "inkscape", "Toolbox")));
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = vec![
+            Rule::new(None, Some("^scratchpad$"), None, RuleAction::Center).unwrap(),
+            Rule::new(Some("^URxvt$"), None, None, RuleAction::AssignTag(Tag::Work(1))).unwrap(),
+            Rule::new(None, None, None, RuleAction::AssignTag(Tag::Work(0))).unwrap(),
+        ];
+
+        assert_eq!(match_rules(&rules, &client("URxvt", "scratchpad", "zsh")),
+                   Some(&RuleAction::Center));
+        assert_eq!(match_rules(&rules, &client("URxvt", "urxvt", "zsh")),
+                   Some(&RuleAction::AssignTag(Tag::Work(1))));
+        assert_eq!(match_rules(&rules, &client("Firefox", "Navigator", "")),
+                   Some(&RuleAction::AssignTag(Tag::Work(0))));
+    }
+
+    #[test]
+    fn no_matching_rule() {
+        let rules = vec![Rule::new(Some("^mpv$"), None, None, RuleAction::SetFloat).unwrap()];
+
+        assert_eq!(match_rules(&rules, &client("Firefox", "Navigator", "")), None);
+        assert_eq!(match_rules(&[], &client("mpv", "gl", "")), None);
+    }
+}
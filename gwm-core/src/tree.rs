@@ -3,10 +3,14 @@
 #![allow(dead_code)]
 
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::Hash;
+use std::mem;
 use std::ops::{Add, Sub, Mul};
 
 use config::Tag;
-use layout::{Geometry, Layout};
+use layout::{ClientSizes, Decorations, Direction, GapConfig, Geometry, Layout, LayoutMessage,
+             SizeHints, Rotation, Struts, apply_borders, apply_size_hints, floating_placement,
+             place_floating, place_fullscreen, resize_constrained, tiled_clients};
 
 use generational_arena::Arena;
 pub use generational_arena::Index as ArenaId;
@@ -22,28 +26,491 @@ pub enum ContainerId {
 pub struct Client<C> {
     id: C,
     currently_mapped: bool,
-    properties: (),
+    properties: ClientProperties,
     tags: HashSet<Tag>,
 }
 
+/// Properties of a client, as read from its window when it is managed.
+///
+/// Floating and fullscreen state, size hints, and the floating geometry are tracked per tag
+/// tree in the client's container instead.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClientProperties {
+    /// The class part of the client's `WM_CLASS` property.
+    pub class: String,
+    /// The instance part of the client's `WM_CLASS` property.
+    pub instance: String,
+    /// The client's title, as given by `_NET_WM_NAME` or `WM_NAME`.
+    pub title: String,
+    /// The process id of the client, as given by `_NET_WM_PID`, if set.
+    pub pid: Option<u32>,
+    /// Whether the client has set the urgency hint in its `WM_HINTS` property.
+    ///
+    /// Cleared when the client is focused using `ClientHierarchy::focus_client`.
+    pub urgent: bool,
+}
+
+impl<C> Client<C> {
+    /// Construct a client record from the properties read from its window.
+    ///
+    /// The client is not mapped and not tagged yet.
+    pub fn new(id: C, properties: ClientProperties) -> Self {
+        Client {
+            id,
+            currently_mapped: false,
+            properties,
+            tags: HashSet::new(),
+        }
+    }
+
+    /// Get the id of the client.
+    pub fn id(&self) -> &C {
+        &self.id
+    }
+
+    /// Get the properties read from the client's window.
+    pub fn properties(&self) -> &ClientProperties {
+        &self.properties
+    }
+
+    /// Get the properties of the client mutably, for instance to update them when they change.
+    pub fn properties_mut(&mut self) -> &mut ClientProperties {
+        &mut self.properties
+    }
+}
+
 pub struct ClientHierarchy<C> {
     screens: Vec<Screen>,
     tagsets: Arena<TagSet<C>>,
     clients: HashMap<C, Client<C>>,
 }
 
+impl<C> ClientHierarchy<C> {
+    /// Toggle whether a tag is shown on a tagset, and signify whether it is shown now.
+    ///
+    /// The clients displayed by the tagset are updated according to their tags, see
+    /// `TagSet::toggle_tag`. Returns `None` if the tagset doesn't exist.
+    pub fn toggle_tag(&mut self, tagset: TagSetId, tag: Tag) -> Option<bool>
+        where C: Clone + Eq + Hash + Ord
+    {
+        let clients = &self.clients;
+
+        self.tagsets.get_mut(tagset).map(|t| t.toggle_tag(tag, clients))
+    }
+
+    /// Focus a client on a tagset, and signify whether a new render is necessary.
+    ///
+    /// The client's urgency hint is cleared. Nothing happens if the tagset doesn't exist or
+    /// doesn't display the client.
+    pub fn focus_client(&mut self, tagset: TagSetId, client: &C) -> bool
+        where C: Eq + Hash
+    {
+        let tree = match self.tagsets.get_mut(tagset) {
+            Some(t) => &mut t.tree,
+            None => return false,
+        };
+
+        let focus_changed = match tree.find_client(client) {
+            Some(id) => tree.focus_client(id),
+            None => return false,
+        };
+
+        let urgency_changed = self
+            .clients
+            .get_mut(client)
+            .is_some_and(|c| mem::replace(&mut c.properties.urgent, false));
+
+        focus_changed || urgency_changed
+    }
+}
+
 pub type TagSetId = ArenaId;
 
 pub struct Screen {
     geometry: Geometry,
+    struts: Struts,
     tagset: TagSetId,
 }
 
+impl Screen {
+    /// Get the area of the screen available to clients, that is, not reserved by panels.
+    pub fn available_area(&self) -> Geometry {
+        self.geometry.subtract_struts(&self.struts)
+    }
+
+    /// Set the space reserved by panels on the screen.
+    pub fn set_struts(&mut self, struts: Struts) {
+        self.struts = struts;
+    }
+}
+
 #[derive(Debug)]
 pub struct TagSet<C> {
     tags: BTreeSet<Tag>,
     tree: TagTree<C>,
     layout: Box<Layout<C>>,
+    gaps: GapConfig,
+    decorations: Decorations,
+}
+
+impl<C> TagSet<C> {
+    /// Render the tagset's tag tree on the given geometry, using its layout and gaps.
+    ///
+    /// Gaps are limited to a quarter of the target geometry's smaller dimension. Tiled
+    /// clients are shrunk to leave room for their borders, and according to their size hints.
+    /// Floating clients not placed by the layout are displayed at their stored geometry, and
+    /// fullscreen clients cover the entire target geometry.
+    pub fn render(&self, target: &Geometry, sizes: &mut ClientSizes) {
+        if self.window_count_deviates() {
+            debug!("layout {} is displaying {} tiled clients, but prefers {:?}",
+                   self.layout.name(), tiled_clients(&self.tree).len(),
+                   self.layout.preferred_window_count());
+        }
+
+        self.layout.render(&self.tree, target, &self.gaps.clamped(target), sizes);
+        apply_borders(&self.tree, &self.decorations, sizes);
+        apply_size_hints(&self.tree, sizes);
+        place_floating(&self.tree, target, sizes);
+        place_fullscreen(&self.tree, target, sizes);
+    }
+
+    /// Get the primary tag of the tagset, which is the lowest of the tags shown.
+    pub fn primary_tag(&self) -> Option<Tag> {
+        self.tags.iter().next().cloned()
+    }
+
+    /// Toggle whether a tag is shown on the tagset, and signify whether it is shown now.
+    ///
+    /// Clients tagged with a newly shown tag are inserted into the tag tree, in the order of
+    /// their ids, and clients left without a shown tag are removed from it. The last tag of a
+    /// tagset can not be removed.
+    pub fn toggle_tag(&mut self, tag: Tag, clients: &HashMap<C, Client<C>>) -> bool
+        where C: Clone + Eq + Hash + Ord
+    {
+        if !self.tags.contains(&tag) {
+            self.tags.insert(tag);
+
+            let mut new_clients: Vec<_> = clients
+                .values()
+                .filter(|c| c.tags.contains(&tag) && self.tree.find_client(&c.id).is_none())
+                .map(|c| c.id.clone())
+                .collect();
+            new_clients.sort();
+
+            for client in new_clients {
+                self.layout.insert_client(&mut self.tree, client);
+            }
+
+            true
+        } else if self.tags.len() > 1 {
+            self.tags.remove(&tag);
+
+            for client in clients.values().filter(|c| c.tags.contains(&tag)) {
+                if !client.tags.iter().any(|t| self.tags.contains(t)) {
+                    self.remove_client(&client.id);
+                }
+            }
+
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Get the window borders drawn around the tagset's clients.
+    pub fn decorations(&self) -> &Decorations {
+        &self.decorations
+    }
+
+    /// Set the window borders drawn around the tagset's clients.
+    pub fn set_decorations(&mut self, decorations: Decorations) {
+        self.decorations = decorations;
+    }
+
+    /// Get the name of the layout used to display the tagset.
+    pub fn layout_name(&self) -> &'static str {
+        self.layout.name()
+    }
+
+    /// Move the focus to the neighbour of the focused client in the given direction.
+    ///
+    /// The neighbour is determined by the layout, so that the sibling cycling directions move
+    /// the focus through all tiled clients in order, wrapping around at the ends. Signifies
+    /// whether the focus changed. Geometric directions are resolved as displayed on `target`.
+    pub fn focus_direction(&mut self, target: &Geometry, dir: Direction) -> bool {
+        let focused = match self.tree.root.get_focused() {
+            Some(f) => ContainerId::Index(f),
+            None => return false,
+        };
+
+        match self.layout.find_container(&self.tree, target, focused, dir) {
+            Some(ContainerId::Index(id)) => self.tree.focus_client(id),
+            _ => false,
+        }
+    }
+
+    /// Find the client container displayed at the given point when rendering on `target`.
+    pub fn container_at_point(&self, target: &Geometry, x: u32, y: u32) -> Option<ContainerId> {
+        self.layout.container_at_point(&self.tree, target, &self.gaps.clamped(target), x, y)
+    }
+
+    /// Check whether the number of tiled clients on the tagset deviates from the number preferred
+    /// by its layout by more than one.
+    pub fn window_count_deviates(&self) -> bool {
+        let count = tiled_clients(&self.tree).len();
+
+        self.layout.preferred_window_count().is_some_and(|p| count.abs_diff(p) > 1)
+    }
+
+    /// Replace the layout used to display the tagset.
+    ///
+    /// The tag tree is transformed to be consistent with the new layout if necessary. Since
+    /// layouts preserve the order of clients when doing so, windows keep their relative
+    /// positions across layout changes.
+    pub fn set_layout(&mut self, layout: Box<dyn Layout<C>>) {
+        if !layout.check_tree(&self.tree) {
+            layout.fixup_tree(&mut self.tree);
+        }
+
+        self.layout = layout;
+    }
+
+    /// Process a modification message and signify whether a new render is necessary.
+    ///
+    /// Gap adjustments and focus changes are handled by the tagset, all other messages are
+    /// passed to the layout. Gaps are limited to a quarter of the smaller dimension of the
+    /// target geometry the tagset is rendered on.
+    pub fn process_msg(&mut self, msg: LayoutMessage, target: &Geometry) -> bool {
+        match msg {
+            LayoutMessage::GapAdjust(delta) => {
+                self.gaps.adjust_inner(delta);
+                self.gaps = self.gaps.clamped(target);
+                true
+            },
+            LayoutMessage::GapSet(gap) => {
+                self.gaps = GapConfig { outer_gap: gap, inner_gap: gap }.clamped(target);
+                true
+            },
+            LayoutMessage::PaddingAdjust(delta) => {
+                self.gaps.adjust_outer(delta);
+                self.gaps = self.gaps.clamped(target);
+                true
+            },
+            LayoutMessage::FocusIndex(index) => match tiled_clients(&self.tree).get(index) {
+                Some(&ContainerId::Index(id)) => self.tree.focus_client(id),
+                _ => false,
+            },
+            LayoutMessage::Rotate(rotation) => self.layout.rotate(&mut self.tree, rotation),
+            msg => self.layout.process_msg(msg),
+        }
+    }
+
+    /// Remove a client from the tagset, and signify whether a new render is necessary.
+    ///
+    /// The layout is notified of the removal, so that it can restructure the tree as it sees
+    /// fit. If the client is not present in the tagset, nothing happens.
+    pub fn remove_client(&mut self, client: &C) -> bool
+        where C: PartialEq
+    {
+        if let Some(id) = self.tree.find_client(client) {
+            self.layout.delete_container(&mut self.tree, ContainerId::Index(id))
+        } else {
+            false
+        }
+    }
+
+    /// Toggle whether a client is floating, and signify whether a new render is necessary.
+    ///
+    /// The tag tree is fixed up if the layout requires it afterwards.
+    pub fn toggle_floating(&mut self, client: &C) -> bool
+        where C: PartialEq
+    {
+        let toggled = match self.tree.find_client(client) {
+            Some(id) => self.tree.toggle_floating(id),
+            None => false,
+        };
+
+        if toggled && !self.layout.check_tree(&self.tree) {
+            self.layout.fixup_tree(&mut self.tree);
+        }
+
+        toggled
+    }
+
+    /// Resize a floating client by the given amounts, and signify whether a new render is
+    /// necessary.
+    ///
+    /// The client keeps its upper left corner and is constrained by its size hints. Tiled
+    /// clients are sized by the layout, and can be resized by adjusting its parameters instead.
+    pub fn resize_floating(&mut self, client: &C, target: &Geometry, dw: i16, dh: i16) -> bool
+        where C: PartialEq
+    {
+        let id = match self.tree.find_client(client) {
+            Some(id) => id,
+            None => return false,
+        };
+
+        let geo = match self.tree.get_container(id) {
+            Some(c) if c.floating() => {
+                let geo = floating_placement(c, target);
+                resize_constrained(&geo, dw.into(), dh.into(), c.size_hints())
+            },
+            _ => return false,
+        };
+
+        self.tree.set_floating_geometry(id, geo)
+    }
+
+    /// Move a floating client by the given offsets, and signify whether a new render is
+    /// necessary.
+    ///
+    /// The client is kept within the target geometry, so that it can't be moved off screen.
+    pub fn move_floating(&mut self, client: &C, target: &Geometry, dx: i16, dy: i16) -> bool
+        where C: PartialEq
+    {
+        let id = match self.tree.find_client(client) {
+            Some(id) => id,
+            None => return false,
+        };
+
+        let geo = match self.tree.get_container(id) {
+            Some(c) if c.floating() => floating_placement(c, target)
+                .translated(dx.into(), dy.into())
+                .clamped_to(target),
+            _ => return false,
+        };
+
+        self.tree.set_floating_geometry(id, geo)
+    }
+
+    /// Toggle whether a client is displayed fullscreen, and signify whether a new render is
+    /// necessary.
+    ///
+    /// The client keeps its position in the tag tree, so that it returns to its previous
+    /// geometry when leaving fullscreen.
+    pub fn toggle_fullscreen(&mut self, client: &C) -> bool
+        where C: PartialEq
+    {
+        match self.tree.find_client(client) {
+            Some(id) => self.tree.toggle_fullscreen(id),
+            None => false,
+        }
+    }
+
+    /// Swap the positions of two clients, and signify whether a new render is necessary.
+    ///
+    /// The focus stays with the focused client. If either client is not present in the
+    /// tagset, nothing happens.
+    pub fn swap_clients(&mut self, a: &C, b: &C) -> bool
+        where C: PartialEq
+    {
+        match (self.tree.find_client(a), self.tree.find_client(b)) {
+            (Some(a), Some(b)) => self.swap_containers(a, b),
+            _ => false,
+        }
+    }
+
+    /// Swap the clients of two containers using the layout, and signify whether a new render
+    /// is necessary.
+    ///
+    /// Focus is tracked by container, so it is moved along with the focused client.
+    fn swap_containers(&mut self, a: ArenaContainerId, b: ArenaContainerId) -> bool {
+        let focused = self.tree.root.get_focused();
+        let (id_a, id_b) = (ContainerId::Index(a), ContainerId::Index(b));
+
+        if !self.layout.swap_containers(&mut self.tree, id_a, id_b) {
+            return false;
+        }
+
+        if focused == Some(a) {
+            self.tree.focus_client(b);
+        } else if focused == Some(b) {
+            self.tree.focus_client(a);
+        }
+
+        true
+    }
+
+    /// Make a client the master client by swapping it with the first tiled client, and
+    /// signify whether a new render is necessary.
+    ///
+    /// The focus stays with the focused client. If the client is the master client already,
+    /// or not tiled on the tagset, nothing happens.
+    pub fn pin_master(&mut self, client: &C) -> bool
+        where C: PartialEq
+    {
+        let id = match self.tree.find_client(client) {
+            Some(id) => id,
+            None => return false,
+        };
+
+        let tiled = tiled_clients(&self.tree);
+
+        match tiled.first() {
+            Some(&ContainerId::Index(master))
+                if master != id && tiled.contains(&ContainerId::Index(id)) =>
+                self.swap_containers(master, id),
+            _ => false,
+        }
+    }
+
+    /// Reorder the tiled clients of the tagset, and signify whether a new render is necessary.
+    ///
+    /// The clients given are moved to the front in order, and all other tiled clients follow
+    /// in their previous order. Clients not tiled on the tagset are ignored. The focus moves
+    /// along with the focused client.
+    pub fn reorder_clients(&mut self, order: &[C]) -> bool
+        where C: PartialEq
+    {
+        let slots = tiled_clients(&self.tree);
+        let clients: Vec<_> = slots
+            .iter()
+            .map(|&id| match id {
+                ContainerId::Index(i) => self.tree.get_container(i).and_then(|c| c.get_client()),
+                ContainerId::Root => None,
+            })
+            .collect();
+
+        let mut target = Vec::with_capacity(slots.len());
+
+        for client in order {
+            if let Some(i) = clients.iter().position(|c| *c == Some(client)) {
+                if !target.contains(&i) {
+                    target.push(i);
+                }
+            }
+        }
+
+        for i in 0..slots.len() {
+            if !target.contains(&i) {
+                target.push(i);
+            }
+        }
+
+        let focused = self.tree.root.focused.map(ContainerId::Index);
+        let focused_pos = slots.iter().position(|&id| Some(id) == focused);
+        let mut current: Vec<_> = (0..slots.len()).collect();
+        let mut changed = false;
+
+        for i in 0..slots.len() {
+            let j = current[i..].iter().position(|&c| c == target[i]).unwrap() + i;
+
+            if j != i {
+                self.tree.swap_clients(slots[i], slots[j]);
+                current.swap(i, j);
+                changed = true;
+            }
+        }
+
+        if let Some(pos) = focused_pos {
+            if let Some(ContainerId::Index(id)) =
+                    target.iter().position(|&t| t == pos).map(|i| slots[i]) {
+                self.tree.focus_client(id);
+            }
+        }
+
+        changed
+    }
 }
 
 // A tag tree.
@@ -75,10 +542,99 @@ impl<C> TagTree<C> {
         }
     }
 
+    /// Get a container in the tree by its id.
+    pub fn get_container(&self, id: ArenaContainerId) -> Option<&Container<C>> {
+        self.containers.get(id)
+    }
+
+    /// Find the container holding the given client, if any.
+    pub fn find_client(&self, client: &C) -> Option<ArenaContainerId>
+        where C: PartialEq
+    {
+        self.containers
+            .iter()
+            .find(|(_, c)| c.get_client() == Some(client))
+            .map(|(i, _)| i)
+    }
+
     pub fn get_cursor(&self) -> Option<ArenaContainerId> {
         self.root.selected.or(self.root.focused)
     }
 
+    /// Toggle whether a client container is floating, and signify whether it exists.
+    ///
+    /// The geometry a client was floating at is retained, so that it reappears there when it
+    /// is made floating again.
+    pub fn toggle_floating(&mut self, id: ArenaContainerId) -> bool {
+        match self.containers.get_mut(id) {
+            Some(Container::Client(c)) => {
+                c.floating = !c.floating;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Set the size hints of a client container, and signify whether it exists.
+    pub fn set_size_hints(&mut self, id: ArenaContainerId, hints: Option<SizeHints>) -> bool {
+        match self.containers.get_mut(id) {
+            Some(Container::Client(c)) => {
+                c.size_hints = hints;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Toggle whether a client container is displayed fullscreen, and signify whether it
+    /// exists.
+    pub fn toggle_fullscreen(&mut self, id: ArenaContainerId) -> bool {
+        match self.containers.get_mut(id) {
+            Some(Container::Client(c)) => {
+                c.fullscreen = !c.fullscreen;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Store the geometry of a floating client, for instance after it has been moved or
+    /// resized, and signify whether the container is a floating client.
+    pub fn set_floating_geometry(&mut self, id: ArenaContainerId, geo: Geometry) -> bool {
+        match self.containers.get_mut(id) {
+            Some(Container::Client(c)) if c.floating => {
+                c.floating_geometry = Some(geo);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Focus a client container, and signify whether the focus changed.
+    ///
+    /// All split containers on the path to the root remember the client as their last focused
+    /// descendant. Split containers and unknown ids can not be focused.
+    pub fn focus_client(&mut self, id: ArenaContainerId) -> bool {
+        if !matches!(self.containers.get(id), Some(Container::Client(_))) {
+            return false;
+        }
+
+        let mut cursor = self.containers[id].get_parent();
+
+        while let Some(ContainerId::Index(p)) = cursor {
+            if let Container::Split(ref mut s) = self.containers[p] {
+                s.last_focused = Some(id);
+            }
+
+            cursor = self.containers[p].get_parent();
+        }
+
+        let changed = self.root.focused != Some(id);
+        self.root.focused = Some(id);
+
+        changed
+    }
+
     pub fn insert_first_client(&mut self, client: C) -> ArenaContainerId {
         assert!(self.root.children.is_none());
 
@@ -99,14 +655,16 @@ impl<C> TagTree<C> {
         let parent = self.containers[cursor].get_parent().expect("cursor is orphaned");
         let mut container = ClientContainer::new(client, parent);
 
+        let prev = self.containers[cursor].get_prev_sibling();
+
+        container.prev_sibling = prev;
         container.next_sibling = Some(cursor);
 
         let id = self.containers.insert(Container::Client(container));
 
         self.containers[cursor].set_prev_sibling(Some(id));
 
-        if let Some(prev) = self.containers[cursor].get_prev_sibling() {
-            self.containers[id].set_prev_sibling(Some(prev));
+        if let Some(prev) = prev {
             self.containers[prev].set_next_sibling(Some(id));
         } else {
             match parent {
@@ -127,14 +685,16 @@ impl<C> TagTree<C> {
         let parent = self.containers[cursor].get_parent().expect("cursor is orphaned");
         let mut container = ClientContainer::new(client, parent);
 
+        let next = self.containers[cursor].get_next_sibling();
+
         container.prev_sibling = Some(cursor);
+        container.next_sibling = next;
 
         let id = self.containers.insert(Container::Client(container));
 
         self.containers[cursor].set_next_sibling(Some(id));
 
-        if let Some(next) = self.containers[cursor].get_next_sibling() {
-            self.containers[id].set_next_sibling(Some(next));
+        if let Some(next) = next {
             self.containers[next].set_prev_sibling(Some(id));
         } else {
             match parent {
@@ -251,11 +811,23 @@ impl<C> TagTree<C> {
         -> ArenaContainerId
     {
         let parent = self.containers[cursor].get_parent().expect("cursor is orphaned");
-        let container = SplitContainer::new(dir, (cursor, cursor));
+        let mut container = SplitContainer::new(dir, (cursor, cursor));
+        container.parent = Some(parent);
+
         let id = self.containers.insert(Container::Split(container));
 
-        let (split, child) = self.containers.get2_mut(id, cursor);
-        split.unwrap().swap_siblings(child.unwrap());
+        if let (Some(split), Some(child)) = self.containers.get2_mut(id, cursor) {
+            split.swap_siblings(child);
+            child.set_parent(Some(ContainerId::Index(id)));
+        }
+
+        if let Some(prev) = self.containers[id].get_prev_sibling() {
+            self.containers[prev].set_next_sibling(Some(id));
+        }
+
+        if let Some(next) = self.containers[id].get_next_sibling() {
+            self.containers[next].set_prev_sibling(Some(id));
+        }
 
         match parent {
             ContainerId::Root => self.root.update_children(cursor, id),
@@ -265,6 +837,197 @@ impl<C> TagTree<C> {
         id
     }
 
+    /// Replace a split container with a single child by that child.
+    ///
+    /// Returns `false` and leaves the tree untouched if the container is not a split container
+    /// with exactly one child.
+    pub fn collapse_split(&mut self, split: ArenaContainerId) -> bool {
+        let child = match self.containers[split] {
+            Container::Split(ref s) if s.children.0 == s.children.1 => s.children.0,
+            _ => return false,
+        };
+
+        let parent = self.containers[split].get_parent().expect("split is orphaned");
+
+        if let (Some(split), Some(child)) = self.containers.get2_mut(split, child) {
+            split.swap_siblings(child);
+            child.set_parent(Some(parent));
+        }
+
+        if let Some(prev) = self.containers[child].get_prev_sibling() {
+            self.containers[prev].set_next_sibling(Some(child));
+        }
+
+        if let Some(next) = self.containers[child].get_next_sibling() {
+            self.containers[next].set_prev_sibling(Some(child));
+        }
+
+        match parent {
+            ContainerId::Root => self.root.update_children(split, child),
+            ContainerId::Index(i) => self.containers[i].update_children(split, child),
+        }
+
+        if self.root.selected == Some(split) {
+            self.root.selected = Some(child);
+        }
+
+        self.containers.remove(split);
+
+        true
+    }
+
+    /// Restructure the tree so that each split container has at most two children.
+    ///
+    /// The tree is flattened first, and the clients are then nested in a sequence of splits,
+    /// each holding a client and the split with all following clients. Split types alternate
+    /// with the nesting depth, starting with the opposite of the root's split type.
+    pub fn binarize(&mut self) {
+        self.flatten();
+
+        let clients: Vec<_> = self.children(ContainerId::Root).map(|(id, _)| id).collect();
+
+        if clients.len() <= 2 {
+            return;
+        }
+
+        let mut rest = clients[clients.len() - 1];
+
+        for depth in (1..clients.len() - 1).rev() {
+            let client = clients[depth];
+            let split_type = if depth % 2 == 1 {
+                self.root.split_type.flipped()
+            } else {
+                self.root.split_type.flipped().flipped()
+            };
+
+            let container = SplitContainer::new(split_type, (client, rest));
+            let id = self.containers.insert(Container::Split(container));
+
+            self.containers[client].set_parent(Some(ContainerId::Index(id)));
+            self.containers[client].set_prev_sibling(None);
+            self.containers[client].set_next_sibling(Some(rest));
+
+            self.containers[rest].set_parent(Some(ContainerId::Index(id)));
+            self.containers[rest].set_prev_sibling(Some(client));
+            self.containers[rest].set_next_sibling(None);
+
+            rest = id;
+        }
+
+        self.containers[clients[0]].set_next_sibling(Some(rest));
+        self.containers[rest].set_parent(Some(ContainerId::Root));
+        self.containers[rest].set_prev_sibling(Some(clients[0]));
+
+        self.root.children = Some((clients[0], rest));
+    }
+
+    /// Restructure the tree so that all client containers are direct children of the root.
+    ///
+    /// The order of clients as given by a preorder traversal is preserved, while all split
+    /// containers are removed from the tree.
+    pub fn flatten(&mut self) {
+        let mut clients = Vec::new();
+        let mut splits = Vec::new();
+
+        for (id, container) in self.preorder(ContainerId::Root) {
+            match container {
+                Container::Split(_) => splits.push(id),
+                Container::Client(_) => clients.push(id),
+            }
+        }
+
+        for id in splits {
+            self.containers.remove(id);
+        }
+
+        if let Some(selected) = self.root.selected {
+            if !self.containers.contains(selected) {
+                self.root.selected = None;
+            }
+        }
+
+        let mut prev = None;
+
+        for &id in &clients {
+            self.containers[id].set_parent(Some(ContainerId::Root));
+            self.containers[id].set_prev_sibling(prev);
+            self.containers[id].set_next_sibling(None);
+
+            if let Some(p) = prev {
+                self.containers[p].set_next_sibling(Some(id));
+            }
+
+            prev = Some(id);
+        }
+
+        self.root.children = match (clients.first(), clients.last()) {
+            (Some(&first), Some(&last)) => Some((first, last)),
+            _ => None,
+        };
+    }
+
+    /// Swap the clients held by two client containers, and signify whether anything changed.
+    ///
+    /// The structure of the tree is left untouched, so that the clients exchange their
+    /// positions. All per-client state, like the floating geometry, moves along with the
+    /// clients. If either container is not a client container, nothing happens.
+    pub fn swap_clients(&mut self, a: ContainerId, b: ContainerId) -> bool {
+        let (a, b) = match (a, b) {
+            (ContainerId::Index(a), ContainerId::Index(b)) if a != b => (a, b),
+            _ => return false,
+        };
+
+        match self.containers.get2_mut(a, b) {
+            (Some(Container::Client(a)), Some(Container::Client(b))) => {
+                mem::swap(&mut a.client, &mut b.client);
+                mem::swap(&mut a.floating, &mut b.floating);
+                mem::swap(&mut a.fullscreen, &mut b.fullscreen);
+                mem::swap(&mut a.size_hints, &mut b.size_hints);
+                mem::swap(&mut a.floating_geometry, &mut b.floating_geometry);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Rotate the clients held by the given client containers by one position, and signify
+    /// whether anything changed.
+    ///
+    /// Clockwise rotation moves each client to the next container in the slice, and the last
+    /// client to the first one. The focus moves along with the focused client.
+    pub fn rotate_clients(&mut self, ids: &[ContainerId], rotation: Rotation) -> bool {
+        let len = ids.len();
+
+        if len < 2 {
+            return false;
+        }
+
+        let focused = self.root.focused.map(ContainerId::Index);
+        let focused_pos = ids.iter().position(|&id| Some(id) == focused);
+
+        match rotation {
+            Rotation::Clockwise => for i in (1..len).rev() {
+                self.swap_clients(ids[i - 1], ids[i]);
+            },
+            Rotation::CounterClockwise => for i in 1..len {
+                self.swap_clients(ids[i - 1], ids[i]);
+            },
+        }
+
+        if let Some(pos) = focused_pos {
+            let new_pos = match rotation {
+                Rotation::Clockwise => (pos + 1) % len,
+                Rotation::CounterClockwise => (pos + len - 1) % len,
+            };
+
+            if let ContainerId::Index(id) = ids[new_pos] {
+                self.focus_client(id);
+            }
+        }
+
+        true
+    }
+
     pub fn delete_container(&mut self, cursor: ContainerId) {
         let mut cursor = match cursor {
             ContainerId::Root => {
@@ -276,9 +1039,20 @@ impl<C> TagTree<C> {
             ContainerId::Index(i) => i,
         };
 
+        // only the subtree we start at has descendants, the ancestors we remove below are left
+        // without children once their only child is gone.
+        let descendants: Vec<_> =
+            self.preorder(ContainerId::Index(cursor)).map(|(id, _)| id).collect();
+
+        for id in descendants {
+            self.containers.remove(id);
+        }
+
         while let Some(parent) = self.containers[cursor].get_parent() {
-            if let Some(prev) = self.containers[cursor].get_prev_sibling() {
-                let succ = self.containers[cursor].get_next_sibling();
+            let pred = self.containers[cursor].get_prev_sibling();
+            let succ = self.containers[cursor].get_next_sibling();
+
+            if let Some(prev) = pred {
                 self.containers[prev].set_next_sibling(succ);
 
                 match parent {
@@ -289,9 +1063,8 @@ impl<C> TagTree<C> {
                 }
             }
 
-            if let Some(next) = self.containers[cursor].get_next_sibling() {
-                let pred = self.containers[cursor].get_prev_sibling();
-                self.containers[next].set_next_sibling(pred);
+            if let Some(next) = succ {
+                self.containers[next].set_prev_sibling(pred);
 
                 match parent {
                     ContainerId::Root =>
@@ -303,12 +1076,36 @@ impl<C> TagTree<C> {
 
             self.containers.remove(cursor);
 
+            // split containers without children are not allowed, so we remove the parent as
+            // well if we just deleted its only child.
             match parent {
-                ContainerId::Index(i) if self.num_children(parent) == 1 =>
+                ContainerId::Root if pred.is_none() && succ.is_none() => {
+                    self.root.children = None;
+                    break;
+                },
+                ContainerId::Index(i) if pred.is_none() && succ.is_none() =>
                     cursor = i,
                 _ => break,
             }
         }
+
+        self.fixup_focus();
+    }
+
+    /// Make sure focus and selection markers point to containers still present in the tree.
+    ///
+    /// If the focused client is gone, the focus falls back to the first client in the tree.
+    fn fixup_focus(&mut self) {
+        if self.root.selected.is_some_and(|s| !self.containers.contains(s)) {
+            self.root.selected = None;
+        }
+
+        if !self.root.focused.is_some_and(|f| self.containers.contains(f)) {
+            self.root.focused = self
+                .preorder(ContainerId::Root)
+                .find(|&(_, c)| matches!(c, Container::Client(_)))
+                .map(|(id, _)| id);
+        }
     }
 
     pub fn preorder(&self, id: ContainerId) -> TagTreePreorder<C> {
@@ -340,7 +1137,7 @@ impl<C> TagTree<C> {
     }
 
     pub fn num_children(&self, id: ContainerId) -> usize {
-        self.children(id).len()
+        self.children(id).count()
     }
 }
 
@@ -353,14 +1150,15 @@ impl<'a, C> Iterator for TagTreeChildren<'a, C> {
     type Item = (ArenaContainerId, &'a Container<C>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.current
-            .and_then(|i| self.tree.containers[i].get_next_sibling())
-            .map(|n| (n, &self.tree.containers[n]))
+        let current = self.current?;
+        let container = &self.tree.containers[current];
+
+        self.current = container.get_next_sibling();
+
+        Some((current, container))
     }
 }
 
-impl<'a, C> ExactSizeIterator for TagTreeChildren<'a, C> { }
-
 pub struct TagTreePreorder<'a, C> {
     tree: &'a TagTree<C>,
     root: ContainerId,
@@ -383,22 +1181,34 @@ impl<'a, C> Iterator for TagTreePreorder<'a, C> {
             ContainerId::Index(current) => {
                 let c = &self.tree.containers[current];
 
-                if let Some(i) = c.get_children().map(|c| c.0).or_else(|| c.get_next_sibling()) {
+                // never leave the subtree we are traversing by visiting the root's siblings
+                let next_sibling = if self.current == self.root {
+                    None
+                } else {
+                    c.get_next_sibling()
+                };
+
+                if let Some(i) = c.get_children().map(|c| c.0).or(next_sibling) {
                     self.current = ContainerId::Index(i);
                     Some((i, &self.tree.containers[i]))
+                } else if self.current == self.root {
+                    None
                 } else {
+                    let mut cursor = current;
+
                     while let Some(ContainerId::Index(i)) =
-                        self.tree.containers[current].get_parent()
+                        self.tree.containers[cursor].get_parent()
                     {
                         if ContainerId::Index(i) == self.root {
                             break;
                         }
 
-                        self.current = ContainerId::Index(i);
-
                         if let Some(n) = self.tree.containers[i].get_next_sibling() {
+                            self.current = ContainerId::Index(n);
                             return Some((n, &self.tree.containers[n]));
                         }
+
+                        cursor = i;
                     }
 
                     None
@@ -531,6 +1341,34 @@ impl<C> Container<C> {
         }
     }
 
+    pub fn fullscreen(&self) -> bool {
+        match self {
+            Self::Client(c) => c.fullscreen,
+            _ => false,
+        }
+    }
+
+    pub fn size_hints(&self) -> Option<&SizeHints> {
+        match self {
+            Self::Client(c) => c.size_hints.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn floating_geometry(&self) -> Option<Geometry> {
+        match self {
+            Self::Client(c) if c.floating => c.floating_geometry,
+            _ => None,
+        }
+    }
+
+    pub fn get_client(&self) -> Option<&C> {
+        match self {
+            Self::Client(c) => Some(&c.client),
+            _ => None,
+        }
+    }
+
     pub fn get_parent(&self) -> Option<ContainerId> {
         match self {
             Self::Split(s) => s.parent,
@@ -715,6 +1553,12 @@ impl SplitContainer {
 pub struct ClientContainer<C> {
     /// Whether the client is floating.
     pub floating: bool,
+    /// Whether the client is displayed fullscreen.
+    pub fullscreen: bool,
+    /// The size hints of the client, if it provided any.
+    size_hints: Option<SizeHints>,
+    /// The geometry the client was last displayed at while floating, if any.
+    floating_geometry: Option<Geometry>,
     /// The client information.
     client: C,
     /// The parent of the container.
@@ -733,6 +1577,9 @@ impl<C> ClientContainer<C> {
     fn new(client: C, parent: ContainerId) -> Self {
         ClientContainer {
             floating: false,
+            fullscreen: false,
+            floating_geometry: None,
+            size_hints: None,
             client,
             parent: Some(parent),
             prev_sibling: None,
@@ -741,32 +1588,48 @@ impl<C> ClientContainer<C> {
     }
 }
 
+/// The ratio of a split, stored in permille to allow for fine-grained adjustments.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct SplitRatio(u8);
+pub struct SplitRatio(u16);
 
 impl SplitRatio {
-    fn new(inner: u8) -> Self {
-        use std::cmp::max;
+    /// Construct a split ratio from a percentage, which is clamped to 100.
+    pub fn new(inner: u8) -> Self {
+        use std::cmp::min;
+
+        SplitRatio(u16::from(min(inner, 100)) * 10)
+    }
+
+    /// Construct a split ratio from a value in permille, which is clamped to 1000.
+    pub fn from_permille(inner: usize) -> Self {
+        use std::cmp::min;
+
+        SplitRatio(min(inner, 1000) as u16)
+    }
 
-        SplitRatio(max(inner, 100))
+    /// Get the value of the split ratio in permille.
+    pub fn permille(&self) -> u16 {
+        self.0
     }
 }
 
-impl Sub<u8> for SplitRatio {
+impl Sub<u16> for SplitRatio {
     type Output = SplitRatio;
 
-    fn sub(self, rhs: u8) -> Self::Output {
+    /// Decrease the ratio by the given amount of permille.
+    fn sub(self, rhs: u16) -> Self::Output {
         SplitRatio(self.0.saturating_sub(rhs))
     }
 }
 
-impl Add<u8> for SplitRatio {
+impl Add<u16> for SplitRatio {
     type Output = SplitRatio;
 
-    fn add(self, rhs: u8) -> Self::Output {
-        use std::cmp::max;
+    /// Increase the ratio by the given amount of permille.
+    fn add(self, rhs: u16) -> Self::Output {
+        use std::cmp::min;
 
-        SplitRatio(max(self.0 + rhs, 100))
+        SplitRatio(min(self.0.saturating_add(rhs), 1000))
     }
 }
 
@@ -774,7 +1637,7 @@ impl Mul<SplitRatio> for u32 {
     type Output = u32;
 
     fn mul(self, rhs: SplitRatio) -> Self::Output {
-        ((self as usize) * 100 / rhs.0 as usize) as u32
+        ((self as usize) * rhs.0 as usize / 1000) as u32
     }
 }
 
@@ -788,3 +1651,396 @@ pub enum SplitType {
     Vertical(SplitRatio),
     Tabbed,
 }
+
+impl SplitType {
+    /// Get the split type with the opposite orientation and an even ratio.
+    ///
+    /// Tabbed splits are treated as if they were horizontal.
+    pub fn flipped(&self) -> SplitType {
+        match *self {
+            SplitType::Vertical(_) => SplitType::Horizontal(SplitRatio::new(50)),
+            _ => SplitType::Vertical(SplitRatio::new(50)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layout::grid::Grid;
+    use layout::insert_client_flat;
+
+    /// Construct a flat tag tree holding the given clients, in order.
+    fn flat_tree(clients: &[u32]) -> TagTree<u32> {
+        let mut tree = TagTree::new(SplitType::Horizontal(SplitRatio::new(50)));
+
+        for &client in clients {
+            insert_client_flat(&mut tree, client);
+
+            let id = tree.find_client(&client).unwrap();
+            tree.focus_client(id);
+        }
+
+        tree
+    }
+
+    /// Construct a tagset displaying the given tag tree with a grid layout.
+    fn tagset(tree: TagTree<u32>) -> TagSet<u32> {
+        TagSet {
+            tags: BTreeSet::new(),
+            tree,
+            layout: Box::new(Grid::new(false)),
+            gaps: GapConfig::none(),
+            decorations: Decorations::default(),
+        }
+    }
+
+    /// Construct a client hierarchy holding a single tagset and the given clients.
+    fn hierarchy(tagset: TagSet<u32>, clients: Vec<Client<u32>>)
+        -> (ClientHierarchy<u32>, TagSetId)
+    {
+        let mut tagsets = Arena::new();
+        let id = tagsets.insert(tagset);
+
+        let hierarchy = ClientHierarchy {
+            screens: Vec::new(),
+            tagsets,
+            clients: clients.into_iter().map(|c| (c.id, c)).collect(),
+        };
+
+        (hierarchy, id)
+    }
+
+    /// Get the client focused on a tagset.
+    fn focused(tagset: &TagSet<u32>) -> Option<u32> {
+        tagset
+            .tree
+            .root
+            .get_focused()
+            .and_then(|id| tagset.tree.get_container(id))
+            .and_then(|c| c.get_client().cloned())
+    }
+
+    /// Get the clients of a tag tree in preorder.
+    fn clients(tree: &TagTree<u32>) -> Vec<u32> {
+        tree.preorder(ContainerId::Root).filter_map(|(_, c)| c.get_client().cloned()).collect()
+    }
+
+    #[test]
+    fn flat_tree_keeps_insertion_order() {
+        assert_eq!(clients(&flat_tree(&[0, 1, 2, 3])), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn delete_from_nested_split_keeps_siblings() {
+        let mut tree = flat_tree(&[0, 1, 2, 3]);
+        let one = tree.find_client(&1).unwrap();
+        let split = tree.split_container(one, SplitType::Vertical(SplitRatio::new(50)));
+        tree.insert_client_after(one, 4);
+        assert_eq!(clients(&tree), vec![0, 1, 4, 2, 3]);
+
+        // deleting the last client of the split must not touch the split's siblings
+        let four = tree.find_client(&4).unwrap();
+        tree.delete_container(ContainerId::Index(four));
+        assert_eq!(clients(&tree), vec![0, 1, 2, 3]);
+        assert_eq!(tree.num_children(ContainerId::Index(split)), 1);
+
+        // deleting the only client left removes the split, but nothing else
+        tree.delete_container(ContainerId::Index(one));
+        assert_eq!(clients(&tree), vec![0, 2, 3]);
+        assert_eq!(tree.len(), 3);
+        assert!(tree.get_container(split).is_none());
+    }
+
+    #[test]
+    fn swap_moves_floating_geometry() {
+        let mut tree = flat_tree(&[0, 1]);
+        let (a, b) = (tree.find_client(&0).unwrap(), tree.find_client(&1).unwrap());
+        let geo = Geometry::new(10, 20, 300, 200);
+
+        tree.toggle_floating(a);
+        tree.set_floating_geometry(a, geo);
+        tree.swap_clients(ContainerId::Index(a), ContainerId::Index(b));
+
+        let floating = tree.find_client(&0).unwrap();
+        assert_eq!(floating, b);
+        assert_eq!(tree.get_container(b).unwrap().floating_geometry(), Some(geo));
+        assert_eq!(tree.get_container(a).unwrap().floating_geometry(), None);
+    }
+
+    #[test]
+    fn swap_moves_fullscreen() {
+        let mut tree = flat_tree(&[0, 1]);
+        let (a, b) = (tree.find_client(&0).unwrap(), tree.find_client(&1).unwrap());
+
+        tree.toggle_fullscreen(a);
+        tree.swap_clients(ContainerId::Index(a), ContainerId::Index(b));
+
+        assert!(!tree.get_container(a).unwrap().fullscreen());
+        assert!(tree.get_container(b).unwrap().fullscreen());
+    }
+
+    #[test]
+    fn swap_moves_size_hints() {
+        let mut tree = flat_tree(&[0, 1]);
+        let (a, b) = (tree.find_client(&0).unwrap(), tree.find_client(&1).unwrap());
+        let hints = SizeHints { min_width: 100, ..SizeHints::default() };
+
+        tree.set_size_hints(a, Some(hints));
+        tree.swap_clients(ContainerId::Index(a), ContainerId::Index(b));
+
+        assert_eq!(tree.get_container(a).unwrap().size_hints(), None);
+        assert_eq!(tree.get_container(b).unwrap().size_hints(), Some(&hints));
+    }
+
+    #[test]
+    fn swap_keeps_focus_on_client() {
+        let mut tagset = tagset(flat_tree(&[0, 1, 2]));
+        assert_eq!(focused(&tagset), Some(2));
+
+        assert!(tagset.swap_clients(&2, &0));
+        assert_eq!(clients(&tagset.tree), vec![2, 1, 0]);
+        assert_eq!(focused(&tagset), Some(2));
+
+        assert!(tagset.swap_clients(&1, &0));
+        assert_eq!(clients(&tagset.tree), vec![2, 0, 1]);
+        assert_eq!(focused(&tagset), Some(2));
+    }
+
+    #[test]
+    fn pin_master_moves_client_with_its_state() {
+        let mut tagset = tagset(flat_tree(&[0, 1, 2]));
+        let hints = SizeHints { min_width: 100, ..SizeHints::default() };
+
+        let id = tagset.tree.find_client(&2).unwrap();
+        tagset.tree.set_size_hints(id, Some(hints));
+        tagset.tree.toggle_fullscreen(id);
+
+        assert!(tagset.pin_master(&2));
+        assert_eq!(clients(&tagset.tree), vec![2, 1, 0]);
+        assert_eq!(focused(&tagset), Some(2));
+
+        let master = tagset.tree.get_container(tagset.tree.find_client(&2).unwrap()).unwrap();
+        assert_eq!(master.size_hints(), Some(&hints));
+        assert!(master.fullscreen());
+
+        let old_master = tagset.tree.get_container(tagset.tree.find_client(&0).unwrap()).unwrap();
+        assert_eq!(old_master.size_hints(), None);
+        assert!(!old_master.fullscreen());
+
+        assert!(!tagset.pin_master(&2));
+        assert_eq!(clients(&tagset.tree), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn remove_client_fixes_focus() {
+        let mut tagset = tagset(flat_tree(&[0, 1, 2, 3]));
+        assert_eq!(focused(&tagset), Some(3));
+
+        // removing an unfocused client leaves the focus alone
+        assert!(tagset.remove_client(&1));
+        assert_eq!(clients(&tagset.tree), vec![0, 2, 3]);
+        assert_eq!(focused(&tagset), Some(3));
+
+        // removing the focused client moves the focus to the first client
+        assert!(tagset.remove_client(&3));
+        assert_eq!(clients(&tagset.tree), vec![0, 2]);
+        assert_eq!(focused(&tagset), Some(0));
+
+        // unknown clients are ignored
+        assert!(!tagset.remove_client(&7));
+        assert_eq!(clients(&tagset.tree), vec![0, 2]);
+
+        assert!(tagset.remove_client(&0));
+        assert!(tagset.remove_client(&2));
+        assert!(clients(&tagset.tree).is_empty());
+        assert_eq!(tagset.tree.root.get_focused(), None);
+    }
+
+    #[test]
+    fn toggle_tag_inserts_and_removes_clients() {
+        let tagged = |id, tags: &[Tag]| {
+            let mut client = Client::new(id, ClientProperties::default());
+            client.tags = tags.iter().cloned().collect();
+            client
+        };
+
+        let mut tagset = tagset(flat_tree(&[0, 1, 3]));
+        tagset.tags.insert(Tag::Work(0));
+
+        let (mut hierarchy, id) = hierarchy(tagset, vec![tagged(0, &[Tag::Work(0)]),
+                                                         tagged(1, &[Tag::Work(0)]),
+                                                         tagged(2, &[Tag::Work(1)]),
+                                                         tagged(3, &[Tag::Work(0), Tag::Work(1)]),
+                                                         tagged(4, &[Tag::NonWork])]);
+
+        assert_eq!(hierarchy.toggle_tag(id, Tag::Work(1)), Some(true));
+        assert_eq!(clients(&hierarchy.tagsets[id].tree), vec![0, 1, 3, 2]);
+
+        // clients still carrying a shown tag stay in the tree
+        assert_eq!(hierarchy.toggle_tag(id, Tag::Work(1)), Some(false));
+        assert_eq!(clients(&hierarchy.tagsets[id].tree), vec![0, 1, 3]);
+
+        assert_eq!(hierarchy.toggle_tag(id, Tag::Work(0)), Some(true));
+        assert_eq!(clients(&hierarchy.tagsets[id].tree), vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn focus_client_clears_urgency() {
+        let urgent = ClientProperties { urgent: true, ..ClientProperties::default() };
+        let (mut hierarchy, id) = hierarchy(tagset(flat_tree(&[0, 1])),
+                                            vec![Client::new(0, urgent.clone()),
+                                                 Client::new(1, urgent)]);
+        assert_eq!(focused(&hierarchy.tagsets[id]), Some(1));
+
+        assert!(hierarchy.focus_client(id, &0));
+        assert_eq!(focused(&hierarchy.tagsets[id]), Some(0));
+        assert!(!hierarchy.clients[&0].properties().urgent);
+        assert!(hierarchy.clients[&1].properties().urgent);
+
+        assert!(!hierarchy.focus_client(id, &0));
+
+        // focusing the focused client still clears its urgency
+        hierarchy.clients.get_mut(&0).unwrap().properties_mut().urgent = true;
+        assert!(hierarchy.focus_client(id, &0));
+        assert!(!hierarchy.clients[&0].properties().urgent);
+
+        assert!(!hierarchy.focus_client(id, &7));
+    }
+
+    #[test]
+    fn focus_index_selects_tiled_client() {
+        let mut tagset = tagset(flat_tree(&[0, 1, 2]));
+        let target = Geometry::new(0, 0, 800, 600);
+
+        assert!(tagset.process_msg(LayoutMessage::FocusIndex(1), &target));
+        assert_eq!(focused(&tagset), Some(1));
+
+        assert!(!tagset.process_msg(LayoutMessage::FocusIndex(3), &target));
+        assert_eq!(focused(&tagset), Some(1));
+    }
+
+    #[test]
+    fn reorder_applies_full_permutation() {
+        let mut tagset = tagset(flat_tree(&[0, 1, 2, 3]));
+
+        assert!(tagset.reorder_clients(&[2, 0, 3, 1]));
+        assert_eq!(clients(&tagset.tree), vec![2, 0, 3, 1]);
+        assert_eq!(focused(&tagset), Some(3));
+
+        assert!(!tagset.reorder_clients(&[2, 0, 3, 1]));
+        assert_eq!(clients(&tagset.tree), vec![2, 0, 3, 1]);
+    }
+
+    #[test]
+    fn reorder_keeps_order_of_remaining_clients() {
+        let mut tagset = tagset(flat_tree(&[0, 1, 2, 3]));
+
+        assert!(tagset.reorder_clients(&[3, 1]));
+        assert_eq!(clients(&tagset.tree), vec![3, 1, 0, 2]);
+        assert_eq!(focused(&tagset), Some(3));
+    }
+
+    #[test]
+    fn reorder_ignores_unknown_clients() {
+        let mut tagset = tagset(flat_tree(&[0, 1, 2, 3]));
+
+        assert!(!tagset.reorder_clients(&[7, 9]));
+        assert_eq!(clients(&tagset.tree), vec![0, 1, 2, 3]);
+
+        // duplicates are ignored as well
+        assert!(tagset.reorder_clients(&[7, 2, 9, 2]));
+        assert_eq!(clients(&tagset.tree), vec![2, 0, 1, 3]);
+        assert_eq!(focused(&tagset), Some(3));
+    }
+
+    #[test]
+    fn rotate_single_client_does_nothing() {
+        let mut tagset = tagset(flat_tree(&[0]));
+        let ids = tiled_clients(&tagset.tree);
+
+        assert!(!tagset.tree.rotate_clients(&ids, Rotation::Clockwise));
+        assert!(!tagset.tree.rotate_clients(&ids, Rotation::CounterClockwise));
+        assert_eq!(clients(&tagset.tree), vec![0]);
+        assert_eq!(focused(&tagset), Some(0));
+    }
+
+    #[test]
+    fn rotate_two_clients_swaps_them() {
+        let mut tagset = tagset(flat_tree(&[0, 1]));
+        let ids = tiled_clients(&tagset.tree);
+
+        assert!(tagset.tree.rotate_clients(&ids, Rotation::Clockwise));
+        assert_eq!(clients(&tagset.tree), vec![1, 0]);
+        assert_eq!(focused(&tagset), Some(1));
+
+        assert!(tagset.tree.rotate_clients(&ids, Rotation::CounterClockwise));
+        assert_eq!(clients(&tagset.tree), vec![0, 1]);
+        assert_eq!(focused(&tagset), Some(1));
+    }
+
+    #[test]
+    fn rotate_three_clients_in_both_directions() {
+        let mut tagset = tagset(flat_tree(&[0, 1, 2]));
+        let ids = tiled_clients(&tagset.tree);
+        tagset.tree.focus_client(tagset.tree.find_client(&1).unwrap());
+
+        assert!(tagset.tree.rotate_clients(&ids, Rotation::Clockwise));
+        assert_eq!(clients(&tagset.tree), vec![2, 0, 1]);
+        assert_eq!(focused(&tagset), Some(1));
+
+        assert!(tagset.tree.rotate_clients(&ids, Rotation::CounterClockwise));
+        assert!(tagset.tree.rotate_clients(&ids, Rotation::CounterClockwise));
+        assert_eq!(clients(&tagset.tree), vec![1, 2, 0]);
+        assert_eq!(focused(&tagset), Some(1));
+    }
+
+    #[test]
+    fn render_subtracts_outer_and_inner_gaps() {
+        let mut tagset = tagset(flat_tree(&[0, 1]));
+        tagset.gaps = GapConfig { outer_gap: 10, inner_gap: 20 };
+
+        let mut sizes = ClientSizes::new();
+        tagset.render(&Geometry::new(0, 0, 800, 600), &mut sizes);
+
+        let geo = |client| sizes[&ContainerId::Index(tagset.tree.find_client(&client).unwrap())];
+
+        // the outer gap separates clients from the screen edges, the inner gap from each other
+        assert_eq!(geo(0), Geometry::new(10, 10, 780, 280));
+        assert_eq!(geo(1), Geometry::new(10, 310, 780, 280));
+    }
+
+    #[test]
+    fn gap_adjustments_are_clamped_to_target() {
+        let mut tagset = tagset(flat_tree(&[0, 1]));
+        let target = Geometry::new(0, 0, 800, 600);
+
+        assert!(tagset.process_msg(LayoutMessage::GapAdjust(100), &target));
+        assert!(tagset.process_msg(LayoutMessage::PaddingAdjust(20), &target));
+        assert_eq!(tagset.gaps, GapConfig { outer_gap: 20, inner_gap: 100 });
+
+        assert!(tagset.process_msg(LayoutMessage::GapAdjust(i16::MAX), &target));
+        assert!(tagset.process_msg(LayoutMessage::PaddingAdjust(200), &target));
+        assert_eq!(tagset.gaps, GapConfig { outer_gap: 150, inner_gap: 150 });
+
+        // decreasing a clamped gap starts from the stored, clamped value
+        assert!(tagset.process_msg(LayoutMessage::GapAdjust(-50), &target));
+        assert!(tagset.process_msg(LayoutMessage::PaddingAdjust(i16::MIN), &target));
+        assert_eq!(tagset.gaps, GapConfig { outer_gap: 0, inner_gap: 100 });
+    }
+
+    #[test]
+    fn gap_set_sets_both_gaps() {
+        let mut tagset = tagset(flat_tree(&[0, 1]));
+        let target = Geometry::new(0, 0, 800, 600);
+
+        assert!(tagset.process_msg(LayoutMessage::GapSet(8), &target));
+        assert_eq!(tagset.gaps, GapConfig { outer_gap: 8, inner_gap: 8 });
+
+        assert!(tagset.process_msg(LayoutMessage::GapSet(1000), &target));
+        assert_eq!(tagset.gaps, GapConfig { outer_gap: 150, inner_gap: 150 });
+
+        assert!(tagset.process_msg(LayoutMessage::GapSet(0), &target));
+        assert_eq!(tagset.gaps, GapConfig::none());
+    }
+}
@@ -33,6 +33,7 @@
  */
 
 use std::fs::File;
+use std::io::ErrorKind;
 use std::io::prelude::*;
 use std::path::Path;
 
@@ -60,6 +61,8 @@ pub fn parse_file(path: &Path) -> KbdResult<Table> {
                 Err(io_error) => Err(KbdError::IOError(io_error)),
             }
         },
+        Err(ref io_error) if io_error.kind() == ErrorKind::NotFound =>
+            Err(KbdError::ConfigNotFound(path.to_owned())),
         Err(io_error) => Err(KbdError::IOError(io_error)),
     }
 }
@@ -73,6 +76,15 @@ pub fn extract_int(table: &mut Table, key: &str) -> KbdResult<i64> {
     }
 }
 
+/// Extract a key's value from a table as a boolean.
+pub fn extract_bool(table: &mut Table, key: &str) -> KbdResult<bool> {
+    match table.remove(key) {
+        Some(Value::Boolean(b)) => Ok(b),
+        Some(_) => Err(KbdError::KeyTypeMismatch(key.to_owned(), false)),
+        None => Err(KbdError::KeyMissing(key.to_owned())),
+    }
+}
+
 /// Extract a key's value from a table as a string.
 pub fn extract_string(table: &mut Table, key: &str) -> KbdResult<String> {
     match table.remove(key) {
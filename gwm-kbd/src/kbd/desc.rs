@@ -32,58 +32,210 @@
  * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
+use std::cmp;
 use std::cmp::Ordering;
 use std::process::Command;
 use std::str::FromStr;
 
-use toml::Value;
+use toml::value::{Table, Value};
 
 use xkb;
 
+use kbd::config;
 use kbd::err::*;
 use kbd::modmask;
 
 /// An index representing a mode.
-pub type Mode = usize;
+///
+/// Modes are created by a `ModeRegistry`, which also keeps track of their names.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Mode(usize);
+
+impl Mode {
+    /// Get the index of the mode, as used to look up mode descriptions.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A registry of all modes known, mapping mode names to modes and vice versa.
+#[derive(Debug, Default)]
+pub struct ModeRegistry {
+    /// The names of all modes, indexed by mode.
+    names: Vec<String>,
+}
+
+impl ModeRegistry {
+    /// Register a mode with the given name, returning the mode.
+    ///
+    /// If a mode with the given name already exists, it is returned instead.
+    pub fn register(&mut self, name: String) -> Mode {
+        if let Some(mode) = self.by_name(&name) {
+            mode
+        } else {
+            self.names.push(name);
+            Mode(self.names.len() - 1)
+        }
+    }
+
+    /// Look up a mode by its name.
+    pub fn by_name(&self, name: &str) -> Option<Mode> {
+        self.names.iter().position(|n| n == name).map(Mode)
+    }
+
+    /// Get the name of a mode.
+    pub fn name(&self, mode: Mode) -> &str {
+        &self.names[mode.0]
+    }
+
+    /// Get an iterator over all modes registered.
+    pub fn modes(&self) -> impl Iterator<Item=Mode> {
+        (0..self.names.len()).map(Mode)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
 
 /// A mode switching action.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum ModeSwitchDesc {
     /// A mode switching action changing the current mode permanently.
     Permanent(Mode),
     /// A temporary mode switching action, changing behaviour only for the next chain.
     Temporary(Mode),
+    /// A mode switching action changing the current mode permanently to the mode with the
+    /// given name.
+    ///
+    /// The name is resolved when the switch is performed.
+    Named(String),
+}
+
+/// The kind of key event a binding reacts to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Trigger {
+    /// The binding is triggered when its last chord is pressed.
+    Press,
+    /// The binding is triggered when its (single) chord is released.
+    Release,
+}
+
+impl Trigger {
+    /// Extract the trigger of a binding from its TOML table, defaulting to key presses.
+    pub fn from_table(table: &mut Table) -> KbdResult<Trigger> {
+        match config::opt_key(config::extract_string(table, "trigger"))? {
+            None => Ok(Trigger::Press),
+            Some(ref t) if t == "press" => Ok(Trigger::Press),
+            Some(ref t) if t == "release" => Ok(Trigger::Release),
+            Some(t) => Err(KbdError::InvalidTrigger(t)),
+        }
+    }
 }
 
 /// A command to be executed in reaction to specific key events.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum CmdDesc {
     /// A string to be passed to a shell to execute the command.
     Shell(String),
     /// A mode to switch to.
     ModeSwitch(ModeSwitchDesc),
+    /// A sequence of commands to run in order. Never contains other sequences.
+    Sequence(Vec<CmdDesc>),
+    /// A command to run a number of times in a row.
+    Repeat {
+        /// The number of times to run the command. Zero is treated as one.
+        count: usize,
+        /// The command to run.
+        cmd: Box<CmdDesc>,
+    },
 }
 
 impl CmdDesc {
     /// Run a command and possibly return an resulting mode switching action to perform.
     pub fn run(&self) -> Option<ModeSwitchDesc> {
+        self.run_with_env(&[])
+    }
+
+    /// Run a command with additional environment variables set for shell commands, and
+    /// possibly return an resulting mode switching action to perform.
+    pub fn run_with_env(&self, env: &[(&str, String)]) -> Option<ModeSwitchDesc> {
         match *self {
             CmdDesc::Shell(ref repr) => {
-                let _ = Command::new("sh").args(&["-c", repr]).spawn();
+                let _ = Command::new("sh")
+                    .args(&["-c", repr])
+                    .envs(env.iter().map(|&(k, ref v)| (k, v)))
+                    .spawn();
                 None
             },
             CmdDesc::ModeSwitch(ref switch) => {
-                Some(*switch)
+                Some(switch.clone())
+            },
+            CmdDesc::Sequence(ref cmds) => {
+                cmds.iter().fold(None, |switch, cmd| cmd.run_with_env(env).or(switch))
+            },
+            CmdDesc::Repeat { count, ref cmd } => {
+                for _ in 0..cmp::max(count, 1) {
+                    if let Some(switch) = cmd.run_with_env(env) {
+                        return Some(switch);
+                    }
+                }
+
+                None
             },
         }
     }
 
     /// Construct a command from a TOML value.
-    pub fn from_value(bind_str: String, value: Value) -> KbdResult<CmdDesc> {
-        if let Value::String(repr) = value {
-            Ok(CmdDesc::Shell(repr))
-        } else {
-            Err(KbdError::KeyTypeMismatch(bind_str, true))
+    ///
+    /// Strings are interpreted as shell commands, as are tables of the form
+    /// `{ cmd = "command" }`. Tables of the form `{ switch_mode = "name" }` switch to the
+    /// named mode, which is looked up when the command is run. If the `temporary` key is set to
+    /// `true` in such a table, the mode switch only lasts for the next chain, and the mode is
+    /// looked up immediately. Arrays of such values are run in order, and the last mode switch
+    /// among them is performed.
+    pub fn from_value(bind_str: String, value: Value, registry: &ModeRegistry)
+        -> KbdResult<CmdDesc>
+    {
+        match value {
+            Value::String(repr) => Ok(CmdDesc::Shell(repr)),
+            Value::Array(values) => {
+                let mut cmds = Vec::with_capacity(values.len());
+
+                for value in values {
+                    match CmdDesc::from_value(bind_str.clone(), value, registry)? {
+                        CmdDesc::Sequence(inner) => cmds.extend(inner),
+                        cmd => cmds.push(cmd),
+                    }
+                }
+
+                Ok(CmdDesc::Sequence(cmds))
+            },
+            Value::Table(mut table) => {
+                if let Some(repr) = config::opt_key(config::extract_string(&mut table, "cmd"))? {
+                    return Ok(CmdDesc::Shell(repr));
+                }
+
+                let name = config::extract_string(&mut table, "switch_mode")?;
+                let temporary =
+                    config::opt_key(config::extract_bool(&mut table, "temporary"))?
+                    .unwrap_or(false);
+
+                if temporary {
+                    let mode = registry
+                        .by_name(&name)
+                        .ok_or(KbdError::UnknownMode(name))?;
+
+                    Ok(CmdDesc::ModeSwitch(ModeSwitchDesc::Temporary(mode)))
+                } else {
+                    Ok(CmdDesc::ModeSwitch(ModeSwitchDesc::Named(name)))
+                }
+            },
+            _ => Err(KbdError::KeyTypeMismatch(bind_str, true)),
         }
     }
 }
@@ -96,6 +248,18 @@ impl KeysymDesc {
     pub fn new(inner: xkb::Keysym) -> Self {
         KeysymDesc(inner)
     }
+
+    /// Get the keysyms of the digit keys from 0 to 9, in order.
+    pub fn digits() -> impl Iterator<Item=KeysymDesc> {
+        let zero: u32 = xkb::key::_0.into();
+
+        (zero..zero + 10).map(|sym| KeysymDesc(xkb::Keysym(sym)))
+    }
+
+    /// Get the value of the digit the keysym represents, if any.
+    pub fn digit(&self) -> Option<usize> {
+        KeysymDesc::digits().position(|sym| sym == *self)
+    }
 }
 
 impl Ord for KeysymDesc {
@@ -112,9 +276,21 @@ impl PartialOrd for KeysymDesc {
     }
 }
 
+impl FromStr for KeysymDesc {
+    type Err = KbdError;
+
+    /// Parse a keysym from its name, falling back to a case-insensitive lookup.
+    fn from_str(s: &str) -> KbdResult<KeysymDesc> {
+        xkb::Keysym::from_str(s)
+            .map(KeysymDesc)
+            .map_err(|()| KbdError::UnknownKeysym(s.to_owned()))
+    }
+}
+
 impl ::std::fmt::Display for KeysymDesc {
+    /// Display the keysym by its name, so that it can be parsed back.
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-        write!(f, "{}", self.0.utf8())
+        write!(f, "{}", self.0)
     }
 }
 
@@ -150,33 +326,36 @@ impl ChordDesc {
     /// Assuming no spaces are present in the string, interpret a sequence of `+`-separated
     /// modifier descriptions, and a single symbol. Interpolates the `$modkey` variable with the
     /// given modifier mask. The part of the string following the first keysym representation is
-    /// discarded.
-    pub fn from_string(desc: &str, modkey_mask: xkb::ModMask) -> KbdResult<ChordDesc> {
+    /// discarded. Modifiers in `ignore_mask` are dropped from the chord.
+    pub fn from_string(desc: &str, modkey_mask: xkb::ModMask, ignore_mask: xkb::ModMask)
+        -> KbdResult<ChordDesc>
+    {
         let mut modmask = xkb::ModMask(0);
 
         for word in desc.split('+') {
             if word == "$modkey" {
                 debug!("added default modifier");
                 modmask::combine(&mut modmask, modkey_mask);
-            } else if modmask::from_str(word, &mut modmask) {
+            } else if modmask::from_str(word, &mut modmask, ignore_mask) {
                 debug!("modifier decoded, continuing chord: {} (modmask={:b})", word, modmask.0);
-            } else if let Ok(sym) = xkb::Keysym::from_str(word) {
-                debug!("keysym decoded, assuming end of chord: {} ({:?})", word, sym);
-                modmask::filter_ignore(&mut modmask);
+            } else {
+                let keysym = KeysymDesc::from_str(word)?;
+                debug!("keysym decoded, assuming end of chord: {} ({})", word, keysym);
+                modmask::filter_ignore(&mut modmask, ignore_mask);
                 return Ok(ChordDesc {
-                    keysym: KeysymDesc(sym),
+                    keysym,
                     modmask,
                 });
-            } else {
-                error!("could not decode keysym or modifier from word, continuing: {}", word);
             }
         }
 
         Err(KbdError::InvalidChord(desc.to_owned()))
     }
 
-    pub fn new(keysym: KeysymDesc, mut modmask: xkb::ModMask) -> ChordDesc {
-        modmask::filter_ignore(&mut modmask);
+    pub fn new(keysym: KeysymDesc, mut modmask: xkb::ModMask, ignore_mask: xkb::ModMask)
+        -> ChordDesc
+    {
+        modmask::filter_ignore(&mut modmask, ignore_mask);
         ChordDesc { keysym, modmask }
     }
 
@@ -187,6 +366,86 @@ impl ChordDesc {
     pub fn modmask(&self) -> u16 {
         self.modmask.0 as u16
     }
+
+    /// Get the value of the digit the chord represents, if it is a digit key without modifiers.
+    pub fn digit(&self) -> Option<usize> {
+        if self.modmask.0 == 0 {
+            self.keysym.digit()
+        } else {
+            None
+        }
+    }
+}
+
+/// A mouse button description.
+///
+/// Like a chord, but with a pointer button pressed instead of a key. Button bindings are
+/// independent of the current mode.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ButtonDesc {
+    /// The number of the button, as used by X.
+    button: u8,
+    /// The modifier mask of the non-depressed mods of the binding.
+    modmask: xkb::ModMask,
+}
+
+impl Ord for ButtonDesc {
+    fn cmp(&self, other: &ButtonDesc) -> Ordering {
+        let modmask: u32 = self.modmask.into();
+
+        self.button.cmp(&other.button).then(modmask.cmp(&other.modmask.into()))
+    }
+}
+
+impl PartialOrd for ButtonDesc {
+    fn partial_cmp(&self, other: &ButtonDesc) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl ButtonDesc {
+    /// Construct a button description from a string representation of modifiers and a button.
+    ///
+    /// The string has the same format as a chord, with the keysym replaced by `button` and
+    /// the button number, as in `$modkey+shift+button1`. Buttons are numbered starting at 1.
+    pub fn from_string(desc: &str, modkey_mask: xkb::ModMask, ignore_mask: xkb::ModMask)
+        -> KbdResult<ButtonDesc>
+    {
+        let mut modmask = xkb::ModMask(0);
+
+        for word in desc.split('+') {
+            if word == "$modkey" {
+                modmask::combine(&mut modmask, modkey_mask);
+            } else if modmask::from_str(word, &mut modmask, ignore_mask) {
+                debug!("modifier decoded, continuing binding: {} (modmask={:b})",
+                       word, modmask.0);
+            } else if let Some(button) = word
+                .strip_prefix("button")
+                .and_then(|b| u8::from_str(b).ok())
+                .filter(|&b| b != 0)
+            {
+                modmask::filter_ignore(&mut modmask, ignore_mask);
+                return Ok(ButtonDesc { button, modmask });
+            } else {
+                break;
+            }
+        }
+
+        Err(KbdError::InvalidChord(desc.to_owned()))
+    }
+
+    pub fn new(button: u8, mut modmask: xkb::ModMask, ignore_mask: xkb::ModMask) -> ButtonDesc {
+        modmask::filter_ignore(&mut modmask, ignore_mask);
+        ButtonDesc { button, modmask }
+    }
+
+    pub fn button(&self) -> u8 {
+        self.button
+    }
+
+    pub fn modmask(&self) -> u16 {
+        self.modmask.0 as u16
+    }
 }
 
 /// A chain description.
@@ -202,11 +461,13 @@ impl ChainDesc {
     /// Construct a chain description from a string representation.
     ///
     /// Interpret the string as a sequence of space-separated strings representing chords.
-    pub fn from_string(desc: &str, modkey_mask: xkb::ModMask) -> KbdResult<ChainDesc> {
+    pub fn from_string(desc: &str, modkey_mask: xkb::ModMask, ignore_mask: xkb::ModMask)
+        -> KbdResult<ChainDesc>
+    {
         let mut chords = Vec::new();
 
         for expr in desc.split(' ') {
-            chords.push(ChordDesc::from_string(expr, modkey_mask)?);
+            chords.push(ChordDesc::from_string(expr, modkey_mask, ignore_mask)?);
         }
 
         Ok(ChainDesc { chords })
@@ -253,11 +514,15 @@ pub struct ModeDesc {
     enter_cmd: Option<CmdDesc>,
     /// An optional command to execute when the given mode is left.
     leave_cmd: Option<CmdDesc>,
+    /// Whether digits typed before a chain are used as a repeat count for its command.
+    numeric_prefix: bool,
 }
 
 impl ModeDesc {
-    pub fn new(enter_cmd: Option<CmdDesc>, leave_cmd: Option<CmdDesc>) -> ModeDesc {
-        ModeDesc { enter_cmd, leave_cmd }
+    pub fn new(enter_cmd: Option<CmdDesc>, leave_cmd: Option<CmdDesc>, numeric_prefix: bool)
+        -> ModeDesc
+    {
+        ModeDesc { enter_cmd, leave_cmd, numeric_prefix }
     }
 
     pub fn enter_cmd(&self) -> Option<&CmdDesc> {
@@ -267,4 +532,100 @@ impl ModeDesc {
     pub fn leave_cmd(&self) -> Option<&CmdDesc> {
         self.leave_cmd.as_ref()
     }
+
+    pub fn numeric_prefix(&self) -> bool {
+        self.numeric_prefix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use xcb::xproto;
+
+    #[test]
+    fn chord_ignores_lock_modifiers() {
+        let num_lock = xkb::ModMask(xproto::MOD_MASK_2);
+        let ignore_mask = modmask::ignore_mask(num_lock);
+        let event_mask =
+            xkb::ModMask(xproto::MOD_MASK_4 | xproto::MOD_MASK_LOCK | xproto::MOD_MASK_2);
+
+        let pressed = ChordDesc::new(KeysymDesc::new(xkb::key::j), event_mask, ignore_mask);
+        let bound = ChordDesc::from_string("mod4+j", xkb::ModMask(0), ignore_mask).unwrap();
+
+        assert_eq!(pressed, bound);
+        assert_eq!(pressed.modmask(), xproto::MOD_MASK_4 as u16);
+    }
+
+    #[test]
+    fn button_parses_modifiers_and_number() {
+        let modkey = xkb::ModMask(xproto::MOD_MASK_4);
+        let ignore_mask = modmask::ignore_mask(xkb::ModMask(xproto::MOD_MASK_2));
+
+        let button = ButtonDesc::from_string("button1", modkey, ignore_mask).unwrap();
+        assert_eq!((button.button(), button.modmask()), (1, 0));
+
+        let button =
+            ButtonDesc::from_string("$modkey+shift+button3", modkey, ignore_mask).unwrap();
+        let modmask = (xproto::MOD_MASK_4 | xproto::MOD_MASK_SHIFT) as u16;
+        assert_eq!((button.button(), button.modmask()), (3, modmask));
+    }
+
+    #[test]
+    fn button_rejects_invalid_numbers() {
+        let modkey = xkb::ModMask(xproto::MOD_MASK_4);
+        let ignore_mask = modmask::ignore_mask(xkb::ModMask(xproto::MOD_MASK_2));
+
+        for desc in &["button0", "button", "button256", "buttonx", "$modkey+button0", "mod4+j"] {
+            assert!(ButtonDesc::from_string(desc, modkey, ignore_mask).is_err(), "{}", desc);
+        }
+    }
+
+    #[test]
+    fn keysym_display_round_trips() {
+        for name in &["a", "Return", "space", "F12", "XF86AudioRaiseVolume", "0"] {
+            let keysym = KeysymDesc::from_str(name).unwrap();
+
+            assert_eq!(keysym.to_string(), *name);
+            assert_eq!(KeysymDesc::from_str(&keysym.to_string()).unwrap(), keysym);
+        }
+
+        // names in the wrong case are accepted, but displayed in their canonical form
+        assert_eq!(KeysymDesc::from_str("return").unwrap().to_string(), "Return");
+        assert!(KeysymDesc::from_str("NoSuchKeysym").is_err());
+    }
+
+    #[test]
+    fn nested_sequences_are_flattened() {
+        let mut registry = ModeRegistry::default();
+        registry.register("normal".to_owned());
+
+        let shell = |repr: &str| Value::String(repr.to_owned());
+        let mut switch = Table::new();
+        switch.insert("switch_mode".to_owned(), shell("normal"));
+
+        let value = Value::Array(vec![
+            shell("a"),
+            Value::Array(vec![shell("b"), Value::Table(switch)]),
+            Value::Array(Vec::new()),
+            shell("c"),
+        ]);
+
+        let cmds = match CmdDesc::from_value("cmd".to_owned(), value, &registry).unwrap() {
+            CmdDesc::Sequence(cmds) => cmds,
+            cmd => panic!("expected a sequence, got {:?}", cmd),
+        };
+
+        let cmds: Vec<_> = cmds
+            .iter()
+            .map(|cmd| match *cmd {
+                CmdDesc::Shell(ref repr) => repr.clone(),
+                CmdDesc::ModeSwitch(ModeSwitchDesc::Named(ref name)) => format!("-> {}", name),
+                ref cmd => panic!("unexpected command in sequence: {:?}", cmd),
+            })
+            .collect();
+
+        assert_eq!(cmds, vec!["a", "b", "-> normal", "c"]);
+    }
 }
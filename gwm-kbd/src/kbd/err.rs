@@ -32,7 +32,10 @@
  * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
+use std::error::Error;
+use std::fmt;
 use std::io::Error as IoError;
+use std::path::PathBuf;
 
 use getopts;
 use toml;
@@ -58,6 +61,10 @@ pub enum XError {
     CouldNotAcquireScreen,
     /// The extension data of the XKB extension could not be determined.
     CouldNotGetExtensionData,
+    /// The XKB events to receive could not be selected.
+    CouldNotSelectEvents(base::GenericError),
+    /// The XKB per-client flags could not be set.
+    CouldNotSetPerClientFlags(base::GenericError),
     /// An I/O error occured.
     IOError,
 }
@@ -66,68 +73,175 @@ impl XError {
     pub fn wrap(self) -> KbdError {
         KbdError::X(self)
     }
+}
 
-    fn handle(self) -> ! {
+impl fmt::Display for XError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use kbd::err::XError::*;
 
-        match self {
-            CouldNotConnect(e) => error!("Could not connect to X server: {}", e),
-            XKBNotSupported => error!("The X server doesn't support XKB"),
-            UseExtensionError(e) => error!("Generic X error: {}", e),
-            CouldNotDetermineCoreDevice => error!("Could not determine core device ID"),
-            CouldNotDetermineKeymap => error!("Could not determine core keymap"),
-            CouldNotDetermineState => error!("Could not determine core state"),
-            CouldNotAcquireScreen => error!("Screen is invalid"),
-            CouldNotGetExtensionData => error!("Could not get XKB extension data"),
-            IOError => error!("An I/O error occured when communicating with the X server"),
+        match *self {
+            CouldNotConnect(ref e) => write!(f, "Could not connect to X server: {}", e),
+            XKBNotSupported => write!(f, "The X server doesn't support XKB"),
+            UseExtensionError(ref e) => write!(f, "Generic X error: {}", e),
+            CouldNotDetermineCoreDevice => write!(f, "Could not determine core device ID"),
+            CouldNotDetermineKeymap => write!(f, "Could not determine core keymap"),
+            CouldNotDetermineState => write!(f, "Could not determine core state"),
+            CouldNotAcquireScreen => write!(f, "Screen is invalid"),
+            CouldNotGetExtensionData => write!(f, "Could not get XKB extension data"),
+            CouldNotSelectEvents(ref e) => write!(f, "Could not select XKB events: {}", e),
+            CouldNotSetPerClientFlags(ref e) =>
+                write!(f, "Could not set XKB per-client flags: {}", e),
+            IOError => write!(f, "An I/O error occured when communicating with the X server"),
         }
+    }
+}
 
-        ::std::process::exit(1);
+impl Error for XError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            XError::CouldNotConnect(ref e) => Some(e),
+            XError::UseExtensionError(ref e) |
+            XError::CouldNotSelectEvents(ref e) |
+            XError::CouldNotSetPerClientFlags(ref e) => Some(e),
+            _ => None,
+        }
     }
 }
 
+/// Log an X protocol error received as an event, which happens for unchecked requests.
+pub fn log_protocol_error(error: &base::GenericError) {
+    // SAFETY: a non-null pointer held by an error points to a complete error structure
+    // allocated by XCB, which lives as long as the error itself.
+    let raw = if let Some(raw) = unsafe { error.ptr.as_ref() } {
+        raw
+    } else {
+        error!("X protocol error without details");
+        return;
+    };
+
+    error!("X protocol error: code={} sequence={} resource={:#x} request={}.{}",
+           error.error_code(), raw.sequence, raw.resource_id, raw.major_code, raw.minor_code);
+}
+
 /// An error occured during operation.
 #[derive(Debug)]
 pub enum KbdError {
     /// Error during command line parsing.
     CouldNotParseOptions(getopts::Fail),
+    /// A command line option has been given an invalid value. Holds the option, the value and
+    /// a description of the values expected.
+    InvalidOptionValue { option: String, value: String, expected: &'static str },
     /// An I/O error occured.
     IOError(IoError),
+    /// The config file doesn't exist.
+    ConfigNotFound(PathBuf),
     /// The TOML content of the config file is invalid.
     TomlError(toml::de::Error),
     /// The TOML file does not contain a toplevel table.
     TomlNotTable,
     /// A necessary config key is missing.
     KeyMissing(String),
+    /// A necessary config key is missing from a section of the config. Holds the section and
+    /// the key.
+    MissingField { section: String, key: String },
     /// A config key holds a value of the wrong type. Second field set to true if it's a command
     /// key.
     KeyTypeMismatch(String, bool),
     /// A Keysym could not be parsed.
     KeysymCouldNotBeParsed(String),
+    /// A word in a chord is neither a modifier nor a known keysym name.
+    UnknownKeysym(String),
     /// An invalid chord has been passed into the config.
     InvalidChord(String),
+    /// A mode referenced in the config doesn't exist.
+    UnknownMode(String),
+    /// A binding has an invalid trigger.
+    InvalidTrigger(String),
+    /// A binding triggered by key release consists of more than one chord.
+    ReleaseBindingIsChain(String),
+    /// A chain starting with a bare digit is bound in a mode reading a numeric prefix.
+    DigitBindingWithNumericPrefix { mode: String, digit: usize },
+    /// Some chains are bound more than once in the same mode. Holds mode names and chains.
+    DuplicateBindings(Vec<(String, String)>),
     /// An error encountered when interacting with X.
     X(XError),
 }
 
 impl KbdError {
+    /// Attach the config section a missing key has been looked up in to the error.
+    ///
+    /// All other errors are returned unchanged.
+    pub fn in_section(self, section: &str) -> KbdError {
+        match self {
+            KbdError::KeyMissing(key) => KbdError::MissingField {
+                section: section.to_owned(),
+                key,
+            },
+            err => err,
+        }
+    }
+
     pub fn handle(self) -> ! {
+        error!("{}", self);
+
+        ::std::process::exit(1);
+    }
+}
+
+impl fmt::Display for KbdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use kbd::err::KbdError::*;
 
-        match self {
-            CouldNotParseOptions(f) => error!("{}", f),
-            IOError(i) => error!("I/O error occured: {}", i),
-            TomlError(t) => error!("TOML parsing of config failed: {}", t),
-            TomlNotTable => error!("config is not a table at the top level"),
-            KeyMissing(k) => error!("missing config key: {}", k),
-            KeyTypeMismatch(k, false) => error!("key {} has incorrect type", k),
-            KeyTypeMismatch(k, true) => error!("command bound to `{}` has non-string type", k),
-            KeysymCouldNotBeParsed(k) => error!("could not parse keysym: {}", k),
-            InvalidChord(d) => error!("chord invalid: {}", d),
-            X(e) => e.handle(),
+        match *self {
+            CouldNotParseOptions(ref o) => write!(f, "{}", o),
+            InvalidOptionValue { ref option, ref value, expected } =>
+                write!(f, "invalid value for option --{} (expected {}): {}",
+                       option, expected, value),
+            IOError(ref i) => write!(f, "I/O error occured: {}", i),
+            ConfigNotFound(ref p) => write!(f, "config file not found: {}", p.display()),
+            TomlError(ref t) => write!(f, "TOML parsing of config failed: {}", t),
+            TomlNotTable => write!(f, "config is not a table at the top level"),
+            KeyMissing(ref k) => write!(f, "missing config key: {}", k),
+            MissingField { ref section, ref key } =>
+                write!(f, "missing config key in section {}: {}", section, key),
+            KeyTypeMismatch(ref k, false) => write!(f, "key {} has incorrect type", k),
+            KeyTypeMismatch(ref k, true) =>
+                write!(f, "command bound to `{}` has non-string type", k),
+            KeysymCouldNotBeParsed(ref k) => write!(f, "could not parse keysym: {}", k),
+            UnknownKeysym(ref k) => write!(f, "unknown keysym: {}", k),
+            InvalidChord(ref d) => write!(f, "chord invalid: {}", d),
+            UnknownMode(ref m) => write!(f, "unknown mode: {}", m),
+            InvalidTrigger(ref t) =>
+                write!(f, "invalid trigger (expected press or release): {}", t),
+            ReleaseBindingIsChain(ref c) =>
+                write!(f, "release bindings need to consist of a single chord: {}", c),
+            DigitBindingWithNumericPrefix { ref mode, digit } =>
+                write!(f, "binding starting with {} in mode {} is shadowed by its numeric prefix",
+                       digit, mode),
+            DuplicateBindings(ref d) => {
+                write!(f, "duplicate bindings:")?;
+
+                for (i, (mode, chain)) in d.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { "," };
+                    write!(f, "{} {} in mode {}", sep, chain, mode)?;
+                }
+
+                Ok(())
+            },
+            X(ref e) => write!(f, "{}", e),
         }
+    }
+}
 
-        ::std::process::exit(1);
+impl Error for KbdError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            KbdError::CouldNotParseOptions(ref o) => Some(o),
+            KbdError::IOError(ref i) => Some(i),
+            KbdError::TomlError(ref t) => Some(t),
+            KbdError::X(ref e) => Some(e),
+            _ => None,
+        }
     }
 }
 
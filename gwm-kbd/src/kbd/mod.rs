@@ -35,6 +35,7 @@
 pub mod config;
 pub mod desc;
 pub mod err;
+pub mod signal;
 pub mod state;
 pub mod modmask;
 
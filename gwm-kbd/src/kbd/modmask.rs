@@ -43,30 +43,39 @@ pub fn combine(mask: &mut xkb::ModMask, add_mask: xkb::ModMask) {
     *mask = xkb::ModMask(mask.0 as xcb_mod_mask_t | add_mask.0 as xcb_mod_mask_t);
 }
 
-const LOCK_MASK: xkb::ModMask=  xkb::ModMask(xproto::MOD_MASK_LOCK);
-const NUM_MASK: xkb::ModMask = xkb::ModMask(xproto::MOD_MASK_2);
-const IGNORE_MASK: xkb::ModMask = xkb::ModMask(xproto::MOD_MASK_LOCK | xproto::MOD_MASK_2);
+const LOCK_MASK: xkb::ModMask = xkb::ModMask(xproto::MOD_MASK_LOCK);
+
+/// Compute the mask of ignored modifiers from the modifier NumLock is mapped to.
+///
+/// CapsLock and NumLock are ignored when matching chords.
+pub fn ignore_mask(num_lock_mask: xkb::ModMask) -> xkb::ModMask {
+    let mut mask = LOCK_MASK;
+
+    combine(&mut mask, num_lock_mask);
+
+    mask
+}
 
 /// Filter ignored modifiers from a mask
-pub fn filter_ignore(mask: &mut xkb::ModMask) {
+pub fn filter_ignore(mask: &mut xkb::ModMask, ignore_mask: xkb::ModMask) {
     use xcb::ffi::xcb_mod_mask_t;
 
-    *mask = xkb::ModMask(mask.0 as xcb_mod_mask_t & !IGNORE_MASK.0);
+    *mask = xkb::ModMask(mask.0 as xcb_mod_mask_t & !ignore_mask.0);
 }
 
 /// Construct a set of modifier masks to grab for a keybinding to account for ignored modifiers.
-pub fn match_ignore(mask: xkb::ModMask) -> [xkb::ModMask; 4] {
+pub fn match_ignore(mask: xkb::ModMask, num_lock_mask: xkb::ModMask) -> [xkb::ModMask; 4] {
     let mut res = [mask, mask, mask, mask];
 
     combine(&mut res[1], LOCK_MASK);
-    combine(&mut res[2], NUM_MASK);
-    combine(&mut res[3], IGNORE_MASK);
+    combine(&mut res[2], num_lock_mask);
+    combine(&mut res[3], ignore_mask(num_lock_mask));
 
     res
 }
 
 /// Get a modifier mask from a string description of the modifier keys.
-pub fn from_str(desc: &str, mask: &mut xkb::ModMask) -> bool {
+pub fn from_str(desc: &str, mask: &mut xkb::ModMask, ignore_mask: xkb::ModMask) -> bool {
     let mut mod_component: xkb::ModMask = xkb::ModMask(match &desc.to_lowercase()[..] {
         "shift" => xproto::MOD_MASK_SHIFT,
         "ctrl" => xproto::MOD_MASK_CONTROL,
@@ -78,7 +87,7 @@ pub fn from_str(desc: &str, mask: &mut xkb::ModMask) -> bool {
         _ => 0,
     });
 
-    filter_ignore(&mut mod_component);
+    filter_ignore(&mut mod_component, ignore_mask);
     combine(mask, mod_component);
 
     mod_component.0 != 0
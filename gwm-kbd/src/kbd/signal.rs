@@ -0,0 +1,116 @@
+/*
+ * Copyright Inokentiy Babushkin and contributors (c) 2016-2017
+ *
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions
+ * are met:
+ *
+ *     * Redistributions of source code must retain the above copyright
+ *       notice, this list of conditions and the following disclaimer.
+ *
+ *     * Redistributions in binary form must reproduce the above
+ *       copyright notice, this list of conditions and the following
+ *       disclaimer in the documentation and/or other materials provided
+ *       with the distribution.
+ *
+ *     * Neither the name of Inokentiy Babushkin nor the names of other
+ *       contributors may be used to endorse or promote products derived
+ *       from this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+ * "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+ * LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+ * A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT
+ * OWNER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+ * SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+ * LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE,
+ * DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY
+ * THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
+ * (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+use libc;
+
+/// Flag set by the signal handler when a reload of the configuration has been requested.
+static RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// The read end of the pipe used to wake up the main loop, or -1 if there is none.
+static WAKEUP_READ: AtomicI32 = AtomicI32::new(-1);
+
+/// The write end of the pipe used to wake up the main loop, or -1 if there is none.
+static WAKEUP_WRITE: AtomicI32 = AtomicI32::new(-1);
+
+/// Signal handler for `SIGUSR1`, only setting a flag and writing to the wakeup pipe to avoid
+/// reentrancy issues.
+extern "C" fn handle_sigusr1(_: libc::c_int) {
+    RELOAD_REQUESTED.store(true, Ordering::SeqCst);
+
+    let fd = WAKEUP_WRITE.load(Ordering::SeqCst);
+
+    if fd >= 0 {
+        unsafe {
+            libc::write(fd, [0u8].as_ptr() as *const libc::c_void, 1);
+        }
+    }
+}
+
+/// Install a handler requesting a configuration reload on `SIGUSR1`.
+///
+/// The handler wakes up the daemon's main loop if it is blocked in `wait_readable`, so that
+/// the request is acted upon immediately.
+pub fn install_reload_handler() {
+    let handler: extern "C" fn(libc::c_int) = handle_sigusr1;
+    let mut fds = [-1; 2];
+
+    unsafe {
+        if libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_CLOEXEC) == 0 {
+            WAKEUP_READ.store(fds[0], Ordering::SeqCst);
+            WAKEUP_WRITE.store(fds[1], Ordering::SeqCst);
+        } else {
+            warn!("could not create wakeup pipe, reloads are delayed until the next event");
+        }
+
+        libc::signal(libc::SIGUSR1, handler as libc::sighandler_t);
+    }
+}
+
+/// Check whether a configuration reload has been requested, and reset the request.
+pub fn reload_requested() -> bool {
+    RELOAD_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// Block until the given file descriptor becomes readable, or a reload is requested.
+///
+/// Spurious wakeups are possible, for instance when the call is interrupted by a signal.
+pub fn wait_readable(fd: RawFd) {
+    let wakeup = WAKEUP_READ.load(Ordering::SeqCst);
+    let mut fds = [
+        libc::pollfd { fd, events: libc::POLLIN, revents: 0 },
+        libc::pollfd { fd: wakeup, events: libc::POLLIN, revents: 0 },
+    ];
+
+    unsafe {
+        libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1);
+    }
+
+    // drain the pipe, so that it doesn't keep waking us up.
+    if fds[1].revents & libc::POLLIN != 0 {
+        let mut buf = [0u8; 64];
+
+        loop {
+            let read = unsafe {
+                libc::read(wakeup, buf.as_mut_ptr() as *mut libc::c_void, buf.len())
+            };
+
+            if read <= 0 {
+                break;
+            }
+        }
+    }
+}
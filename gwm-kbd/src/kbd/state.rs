@@ -33,7 +33,8 @@
  */
 
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
 
 use toml::value::Value;
 
@@ -51,6 +52,7 @@ use kbd::config;
 use kbd::desc::*;
 use kbd::err::*;
 use kbd::modmask;
+use kbd::signal;
 
 /// Keyboard state object.
 pub struct KbdState<'a> {
@@ -72,6 +74,8 @@ pub struct KbdState<'a> {
     max_keycode: Keycode,
     /// Map from keycodes in the index to keysyms the corresponding keys yield.
     keysym_map: Vec<Option<KeysymDesc>>,
+    /// The modifier mask NumLock is mapped to.
+    num_lock_mask: xkb::ModMask,
 }
 
 impl<'a> KbdState<'a> {
@@ -98,14 +102,19 @@ impl<'a> KbdState<'a> {
             min_keycode: setup.min_keycode().into(),
             max_keycode: setup.max_keycode().into(),
             keysym_map: Vec::new(),
+            num_lock_mask: xkb::ModMask(0),
         };
 
         state.generate_keysym_map();
+        state.num_lock_mask = state.determine_num_lock_mask();
 
         Ok(state)
     }
 
     /// Update keymap and keyboard state.
+    ///
+    /// The keysym map and the NumLock modifier are determined anew, since both depend on the
+    /// keymap.
     fn update_keymap(&mut self) -> KbdResult<()> {
         use xkb::x11 as x11;
 
@@ -124,6 +133,11 @@ impl<'a> KbdState<'a> {
             Err(()) => return Err(XError::CouldNotDetermineState.wrap()),
         };
 
+        self.dummy_state = self.keymap.state();
+        self.keysym_map.clear();
+        self.generate_keysym_map();
+        self.num_lock_mask = self.determine_num_lock_mask();
+
         Ok(())
     }
 
@@ -176,6 +190,42 @@ impl<'a> KbdState<'a> {
             .map(|pos| Keycode(self.min_keycode.0 + (pos as u32)))
     }
 
+    /// Determine the modifier mask NumLock is mapped to by querying the modifier map.
+    ///
+    /// Returns an empty mask if NumLock isn't mapped to any modifier.
+    fn determine_num_lock_mask(&self) -> xkb::ModMask {
+        let num_lock = if let Some(keycode) =
+                self.lookup_keysym(KeysymDesc::new(xkb::key::Num_Lock)) {
+            keycode
+        } else {
+            return xkb::ModMask(0);
+        };
+
+        let reply = match xproto::get_modifier_mapping(self.con).get_reply() {
+            Ok(r) => r,
+            Err(e) => {
+                error!("could not query modifier mapping: {}", e);
+                return xkb::ModMask(0);
+            },
+        };
+
+        let per_modifier = reply.keycodes_per_modifier() as usize;
+
+        for (i, keycodes) in reply.keycodes().chunks(per_modifier.max(1)).enumerate() {
+            if keycodes.iter().any(|k| u32::from(*k) == num_lock.0) {
+                debug!("determined numlock modifier: {}", i);
+                return xkb::ModMask(1 << i);
+            }
+        }
+
+        xkb::ModMask(0)
+    }
+
+    /// Get the mask of modifiers to ignore when matching chords.
+    fn ignore_mask(&self) -> xkb::ModMask {
+        modmask::ignore_mask(self.num_lock_mask)
+    }
+
     /// Get the connection to the X server.
     fn con(&self) -> &Connection {
         self.con
@@ -209,29 +259,61 @@ pub struct DaemonState<'a> {
     current_mode: Mode,
     /// The previous mode to switch back to for when the current mode is set temporarily.
     previous_mode: Option<Mode>,
+    /// The key that set the current mode temporarily, if it is still held down.
+    temporary_key: Option<Keycode>,
     /// The vector of all modes the daemon is aware of.
     modes: Vec<ModeDesc>,
+    /// The names of all modes the daemon is aware of.
+    mode_registry: ModeRegistry,
     /// The main modkey to use.
     modkey_mask: xkb::ModMask,
     /// The maximum time between two keypresses in a chain in milliseconds.
     keypress_timeout: u32,
     /// Currently active chain prefix.
     current_chain: ChainDesc,
+    /// The repeat count typed before the current chain, if any.
+    count_prefix: Option<usize>,
     /// Time at which the last key was pressed.
     last_keypress: Timestamp,
     /// The bindings registered in all modes.
-    bindings: BTreeMap<(Mode, ChainDesc), CmdDesc>,
+    bindings: BTreeMap<(Mode, Trigger, ChainDesc), CmdDesc>,
+    /// The mouse button bindings, active in all modes.
+    buttons: BTreeMap<ButtonDesc, CmdDesc>,
+    /// The path to the configuration file, used when reloading it.
+    config_path: PathBuf,
+    /// Whether bindings defined more than once are treated as an error.
+    strict: bool,
 }
 
-impl<'a> DaemonState<'a> {
-    /// Construct an initial daemon state from a configuration file.
-    pub fn from_config(path: &Path, kbd_state: KbdState<'a>) -> KbdResult<Self> {
+/// The parts of the daemon state determined by the configuration file.
+#[derive(Debug)]
+struct ConfigState {
+    /// The vector of all modes the daemon is aware of.
+    modes: Vec<ModeDesc>,
+    /// The names of all modes the daemon is aware of.
+    mode_registry: ModeRegistry,
+    /// The main modkey to use.
+    modkey_mask: xkb::ModMask,
+    /// The maximum time between two keypresses in a chain in milliseconds.
+    keypress_timeout: u32,
+    /// The bindings registered in all modes.
+    bindings: BTreeMap<(Mode, Trigger, ChainDesc), CmdDesc>,
+    /// The mouse button bindings, active in all modes.
+    buttons: BTreeMap<ButtonDesc, CmdDesc>,
+}
+
+impl ConfigState {
+    /// Parse a configuration file, ignoring the modifiers in `ignore_mask` in all chords.
+    ///
+    /// If `strict` is set, bindings defined more than once are treated as an error. Otherwise,
+    /// the last definition of such a binding is used.
+    fn from_file(path: &Path, ignore_mask: xkb::ModMask, strict: bool) -> KbdResult<Self> {
         let mut tree = config::parse_file(path)?;
         info!("parsed config");
 
         let modkey_str = config::extract_string(&mut tree, "modkey")?;
         let mut modkey_mask = xkb::ModMask(0);
-        if modmask::from_str(&modkey_str, &mut modkey_mask) {
+        if modmask::from_str(&modkey_str, &mut modkey_mask, ignore_mask) {
             info!("determined modkey mask: {} ({:x})", modkey_str, modkey_mask.0);
         } else {
             error!("could not decode modkey keysym from word, aborting: {}", modkey_str);
@@ -243,69 +325,235 @@ impl<'a> DaemonState<'a> {
             config::opt_key(config::extract_int(&mut tree, "timeout"))?.unwrap_or(1000) as u32;
 
         let mode_set = config::extract_array(&mut tree, "active_modes")?;
-        let num_modes = mode_set.len();
+        let mut mode_registry = ModeRegistry::default();
+
+        // register all modes first, so that bindings can refer to modes defined later.
+        for (i, mode_name) in mode_set.into_iter().enumerate() {
+            if let Value::String(s) = mode_name {
+                mode_registry.register(s);
+            } else {
+                return Err(KbdError::KeyTypeMismatch(format!("active_modes.{}", i), false));
+            }
+        }
 
         let mut mode_table = config::extract_table(&mut tree, "modes")?;
-        let mut i = 0;
 
-        let mut modes = Vec::with_capacity(num_modes);
+        let mut modes = Vec::with_capacity(mode_registry.len());
         let mut bindings = BTreeMap::new();
+        let mut duplicates = Vec::new();
 
-        for mode_name in mode_set {
-            let mode_name = if let Value::String(s) = mode_name {
-                s
-            } else {
-                return Err(KbdError::KeyTypeMismatch(format!("active_modes.{}", i), false));
-            };
-
-            let mut mode = config::extract_table(&mut mode_table, &mode_name)?;
+        for i in mode_registry.modes() {
+            let mode_name = mode_registry.name(i);
+            let mut mode = config::extract_table(&mut mode_table, mode_name)
+                .map_err(|e| e.in_section("modes"))?;
+            let section = format!("modes.{}", mode_name);
 
-            let enter_binding = config::extract_string(&mut mode, "enter_binding")?;
+            let enter_binding = config::extract_string(&mut mode, "enter_binding")
+                .map_err(|e| e.in_section(&section))?;
             let enter_binding_quick =
-                config::extract_string(&mut mode, "enter_binding_quick_leave")?;
+                config::extract_string(&mut mode, "enter_binding_quick_leave")
+                .map_err(|e| e.in_section(&section))?;
             let enter_cmd = config::opt_key(config::extract_string(&mut mode, "enter_cmd"))?
                 .map(CmdDesc::Shell);
             let leave_cmd = config::opt_key(config::extract_string(&mut mode, "leave_cmd"))?
                 .map(CmdDesc::Shell);
+            let numeric_prefix =
+                config::opt_key(config::extract_bool(&mut mode, "numeric_prefix"))?
+                .unwrap_or(false);
 
             debug!("mode: {}", mode_name);
 
-            modes.push(ModeDesc::new(enter_cmd, leave_cmd));
+            modes.push(ModeDesc::new(enter_cmd, leave_cmd, numeric_prefix));
 
-            let binds = config::extract_table(&mut mode, "bindings")?;
+            let binds = config::extract_table(&mut mode, "bindings")
+                .map_err(|e| e.in_section(&section))?;
 
-            for (chain_str, cmd_str) in binds {
+            for (chain_str, mut cmd_str) in binds {
                 debug!("=> {} -> {}", chain_str, cmd_str);
-                bindings
-                    .insert((i, ChainDesc::from_string(&chain_str, modkey_mask)?),
-                            CmdDesc::from_value(chain_str, cmd_str)?);
+                let chain = ChainDesc::from_string(&chain_str, modkey_mask, ignore_mask)?;
+                let trigger = if let Value::Table(ref mut t) = cmd_str {
+                    Trigger::from_table(t)?
+                } else {
+                    Trigger::Press
+                };
+
+                if trigger == Trigger::Release && chain.len() != 1 {
+                    return Err(KbdError::ReleaseBindingIsChain(chain_str));
+                }
+
+                let cmd = CmdDesc::from_value(chain_str.clone(), cmd_str, &mode_registry)?;
+
+                if bindings.insert((i, trigger, chain), cmd).is_some() {
+                    duplicates.push((mode_name.to_owned(), chain_str));
+                }
+            }
+
+            for j in mode_registry.modes() {
+                let enter = ChainDesc::from_string(&enter_binding, modkey_mask, ignore_mask)?;
+                let enter_quick =
+                    ChainDesc::from_string(&enter_binding_quick, modkey_mask, ignore_mask)?;
+
+                let enter_cmd = CmdDesc::ModeSwitch(ModeSwitchDesc::Permanent(i));
+                let enter_quick_cmd = CmdDesc::ModeSwitch(ModeSwitchDesc::Temporary(i));
+
+                if bindings.insert((j, Trigger::Press, enter), enter_cmd).is_some() {
+                    duplicates.push((mode_registry.name(j).to_owned(), enter_binding.clone()));
+                }
+
+                if bindings.insert((j, Trigger::Press, enter_quick), enter_quick_cmd).is_some() {
+                    duplicates
+                        .push((mode_registry.name(j).to_owned(), enter_binding_quick.clone()));
+                }
+            }
+        }
+
+        // bare digits are read as a repeat count in modes with a numeric prefix, so chains
+        // starting with one could never be triggered in these modes.
+        for &(mode, trigger, ref chain) in bindings.keys() {
+            let digit = chain.chords().first().and_then(ChordDesc::digit);
+
+            if let (Some(digit), Trigger::Press) = (digit, trigger) {
+                if modes[mode.index()].numeric_prefix() {
+                    let mode = mode_registry.name(mode).to_owned();
+                    return Err(KbdError::DigitBindingWithNumericPrefix { mode, digit });
+                }
+            }
+        }
+
+        let mouse = config::opt_key(config::extract_array(&mut tree, "mouse"))?
+            .unwrap_or_default();
+        let mut buttons = BTreeMap::new();
+
+        for (i, binding) in mouse.into_iter().enumerate() {
+            let section = format!("mouse.{}", i);
+            let mut binding = if let Value::Table(t) = binding {
+                t
+            } else {
+                return Err(KbdError::KeyTypeMismatch(section, false));
+            };
+
+            let button_str = config::extract_string(&mut binding, "binding")
+                .map_err(|e| e.in_section(&section))?;
+            let cmd_value = binding
+                .remove("cmd")
+                .ok_or_else(|| KbdError::KeyMissing("cmd".to_owned()).in_section(&section))?;
+
+            debug!("mouse: {} -> {}", button_str, cmd_value);
+            let button = ButtonDesc::from_string(&button_str, modkey_mask, ignore_mask)?;
+            let cmd = CmdDesc::from_value(button_str.clone(), cmd_value, &mode_registry)?;
+
+            if buttons.insert(button, cmd).is_some() {
+                duplicates.push(("mouse".to_owned(), button_str));
             }
+        }
 
-            for j in 0..num_modes {
-                bindings
-                    .insert((j, ChainDesc::from_string(&enter_binding, modkey_mask)?),
-                            CmdDesc::ModeSwitch(ModeSwitchDesc::Permanent(i)));
-                bindings
-                    .insert((j, ChainDesc::from_string(&enter_binding_quick, modkey_mask)?),
-                            CmdDesc::ModeSwitch(ModeSwitchDesc::Temporary(i)));
+        if !duplicates.is_empty() {
+            if strict {
+                return Err(KbdError::DuplicateBindings(duplicates));
             }
 
-            i += 1;
+            for (mode, chain) in duplicates {
+                warn!("duplicate binding in mode {}, using last definition: {}", mode, chain);
+            }
         }
 
-        Ok(DaemonState {
-            kbd_state,
-            current_mode: 0,
-            previous_mode: None,
+        Ok(ConfigState {
             modes,
+            mode_registry,
             modkey_mask,
             keypress_timeout,
+            bindings,
+            buttons,
+        })
+    }
+}
+
+impl<'a> DaemonState<'a> {
+    /// Construct an initial daemon state from a configuration file.
+    ///
+    /// If `strict` is set, bindings defined more than once are treated as an error. Otherwise,
+    /// the last definition of such a binding is used.
+    pub fn from_config(path: &Path, kbd_state: KbdState<'a>, strict: bool) -> KbdResult<Self> {
+        let config = ConfigState::from_file(path, kbd_state.ignore_mask(), strict)?;
+
+        Ok(DaemonState {
+            kbd_state,
+            current_mode: Mode::default(),
+            previous_mode: None,
+            temporary_key: None,
+            modes: config.modes,
+            mode_registry: config.mode_registry,
+            modkey_mask: config.modkey_mask,
+            keypress_timeout: config.keypress_timeout,
             current_chain: ChainDesc::default(),
+            count_prefix: None,
             last_keypress: 0,
-            bindings,
+            bindings: config.bindings,
+            buttons: config.buttons,
+            config_path: path.to_owned(),
+            strict,
         })
     }
 
+    /// Reload the configuration file the daemon has been started with.
+    ///
+    /// If the new configuration can't be loaded, the error is logged and the old bindings are
+    /// kept. Otherwise, the daemon stays in the current mode if it still exists, and falls back
+    /// to the first mode if it doesn't. No enter or leave commands are run in either case.
+    pub fn reload_config(&mut self) {
+        let ignore_mask = self.kbd_state.ignore_mask();
+        let config = match ConfigState::from_file(&self.config_path, ignore_mask, self.strict) {
+            Ok(c) => c,
+            Err(e) => {
+                error!("could not reload config, keeping old bindings: {}", e);
+                return;
+            },
+        };
+
+        self.ungrab_current_mode();
+        self.ungrab_buttons();
+
+        let current_name = self.mode_registry.name(self.current_mode).to_owned();
+        self.current_mode = config.mode_registry.by_name(&current_name).unwrap_or_default();
+        self.previous_mode = None;
+        self.temporary_key = None;
+        self.modes = config.modes;
+        self.mode_registry = config.mode_registry;
+        self.modkey_mask = config.modkey_mask;
+        self.keypress_timeout = config.keypress_timeout;
+        self.current_chain.clear();
+        self.count_prefix = None;
+        self.bindings = config.bindings;
+        self.buttons = config.buttons;
+
+        self.grab_current_mode();
+        self.grab_buttons();
+        info!("reloaded config, now in mode {}", self.mode_registry.name(self.current_mode));
+    }
+
+    /// Update the keymap after the keyboard changed, and grab the bindings anew.
+    ///
+    /// If NumLock has been mapped to a different modifier, the configuration is reloaded, so
+    /// that the new modifier is ignored in all bindings.
+    fn update_keymap(&mut self) -> KbdResult<()> {
+        let num_lock_mask = self.kbd_state.num_lock_mask;
+
+        // keycodes might change along with the keymap, so the old grabs are released first.
+        self.ungrab_current_mode();
+        self.ungrab_buttons();
+        let res = self.kbd_state.update_keymap();
+
+        if res.is_ok() && self.kbd_state.num_lock_mask != num_lock_mask {
+            info!("NumLock modifier changed, reloading config");
+            self.reload_config();
+        }
+
+        self.grab_current_mode();
+        self.grab_buttons();
+
+        res
+    }
+
     /// Get the connection to the X server.
     fn con(&self) -> &Connection {
         self.kbd_state.con()
@@ -317,26 +565,36 @@ impl<'a> DaemonState<'a> {
     }
 
     /// Grab keys for the current mode.
+    ///
+    /// The keyboard is grabbed synchronously, so that key presses not bound can be replayed.
     pub fn grab_current_mode(&self) {
+        let mut chords: Vec<(KeysymDesc, u16)> = self.bindings
+            .keys()
+            .filter(|&&(mode, _, _)| mode == self.current_mode)
+            .flat_map(|(_, _, chain)| chain.chords())
+            .map(|chord| (chord.keysym(), chord.modmask()))
+            .collect();
+
+        // digits typed before a chain need to be grabbed without modifiers.
+        if self.modes[self.current_mode.index()].numeric_prefix() {
+            chords.extend(KeysymDesc::digits().map(|keysym| (keysym, 0)));
+        }
+
         let mut cookies = Vec::new();
 
-        for &(mode, ref chain) in self.bindings.keys() {
-            if mode == self.current_mode {
-                for chord in chain.chords() {
-                    if let Some(keycode) = self.kbd_state.lookup_keysym(chord.keysym()) {
-                        let masks =
-                            modmask::match_ignore(xkb::ModMask(u32::from(chord.modmask())));
-
-                        for mask in &masks {
-                            debug!("grabbing: {:8b}+{} ({})", mask.0, keycode.0, chord.keysym());
-                            let cookie =
-                                xproto::grab_key(self.con(), true, self.root(),
-                                                 mask.0 as u16, keycode.0 as u8,
-                                                 xproto::GRAB_MODE_SYNC as u8,
-                                                 xproto::GRAB_MODE_ASYNC as u8);
-                            cookies.push(cookie);
-                        }
-                    }
+        for (keysym, modmask) in chords {
+            if let Some(keycode) = self.kbd_state.lookup_keysym(keysym) {
+                let masks = modmask::match_ignore(xkb::ModMask(u32::from(modmask)),
+                                                  self.kbd_state.num_lock_mask);
+
+                for mask in &masks {
+                    debug!("grabbing: {:8b}+{} ({})", mask.0, keycode.0, keysym);
+                    let cookie =
+                        xproto::grab_key(self.con(), true, self.root(),
+                                         mask.0 as u16, keycode.0 as u8,
+                                         xproto::GRAB_MODE_ASYNC as u8,
+                                         xproto::GRAB_MODE_SYNC as u8);
+                    cookies.push(cookie);
                 }
             }
         }
@@ -364,18 +622,65 @@ impl<'a> DaemonState<'a> {
         }
     }
 
+    /// Grab the mouse buttons bound, which are independent of the current mode.
+    pub fn grab_buttons(&self) {
+        let mut cookies = Vec::new();
+
+        for button in self.buttons.keys() {
+            let masks = modmask::match_ignore(xkb::ModMask(u32::from(button.modmask())),
+                                              self.kbd_state.num_lock_mask);
+
+            for mask in &masks {
+                debug!("grabbing: {:8b}+button{}", mask.0, button.button());
+                let cookie =
+                    xproto::grab_button(self.con(), false, self.root(),
+                                        xproto::EVENT_MASK_BUTTON_PRESS as u16,
+                                        xproto::GRAB_MODE_ASYNC as u8,
+                                        xproto::GRAB_MODE_ASYNC as u8,
+                                        NONE, NONE,
+                                        button.button(), mask.0 as u16);
+                cookies.push(cookie);
+            }
+        }
+
+        for cookie in cookies {
+            if let Err(e) = cookie.request_check() {
+                error!("encountered error grabbing buttons: {}", e);
+            }
+        }
+    }
+
+    /// Ungrab all mouse buttons.
+    fn ungrab_buttons(&self) {
+        let err = xproto::ungrab_button(self.con(),
+                                        xproto::BUTTON_INDEX_ANY as u8,
+                                        self.root(),
+                                        xproto::MOD_MASK_ANY as u16)
+            .request_check()
+            .is_err();
+
+        if err {
+            error!("could not ungrab buttons");
+        }
+    }
+
     /// Fall back to a mode possibly stored in the `previous_mode` field.
     fn fallback_mode(&mut self) {
+        self.temporary_key = None;
+
         if let Some(fallback_mode) = self.previous_mode {
             info!("falling back to mode");
-            self.switch_mode(ModeSwitchDesc::Permanent(fallback_mode));
+            let _ = self.switch_mode(ModeSwitchDesc::Permanent(fallback_mode));
         }
     }
 
     /// Switch modes according to directive.
     ///
-    /// Manages internal state, as well as necessary interaction with the X server.
-    fn switch_mode(&mut self, switch: ModeSwitchDesc) {
+    /// Manages internal state, as well as necessary interaction with the X server. Fails if
+    /// a mode to switch to by name doesn't exist.
+    fn switch_mode(&mut self, switch: ModeSwitchDesc) -> KbdResult<()> {
+        self.temporary_key = None;
+
         let new_mode = match switch {
             ModeSwitchDesc::Permanent(new_mode) => {
                 self.previous_mode = None;
@@ -385,31 +690,47 @@ impl<'a> DaemonState<'a> {
                 self.previous_mode = Some(self.current_mode);
                 new_mode
             },
+            ModeSwitchDesc::Named(name) => {
+                let new_mode = self
+                    .mode_registry
+                    .by_name(&name)
+                    .ok_or(KbdError::UnknownMode(name))?;
+                self.previous_mode = None;
+                new_mode
+            },
         };
 
         if new_mode == self.current_mode {
             self.previous_mode = None;
-            return;
+            return Ok(());
         }
 
-        if let Some(cmd) = self.modes[self.current_mode].leave_cmd() {
+        if let Some(cmd) = self.modes[self.current_mode.index()].leave_cmd() {
             cmd.run();
         }
 
+        info!("switching to mode {}", self.mode_registry.name(new_mode));
         self.current_mode = new_mode;
 
-        if let Some(cmd) = self.modes[self.current_mode].enter_cmd() {
+        if let Some(cmd) = self.modes[self.current_mode.index()].enter_cmd() {
             cmd.run();
         }
 
         self.ungrab_current_mode();
         self.grab_current_mode();
+
+        Ok(())
     }
 
     /// Process a chord determined from a key press event.
     ///
     /// Dispatches to command execution and mode switching logic according to configuration.
-    fn process_chord(&mut self, keycode: Keycode, time: xproto::Timestamp) {
+    /// A mode set temporarily is left after the next chain, unless the key that set it is still
+    /// held down, in which case it is left once that key is released.
+    ///
+    /// Signifies whether the key has been consumed. Keys that neither continue a chain nor have
+    /// a release binding are passed on to the focused client instead.
+    fn process_chord(&mut self, keycode: Keycode, time: xproto::Timestamp) -> bool {
         let keysym = if let Some(sym) = self.kbd_state.lookup_keycode(keycode) {
             debug!("key pressed:: keycode={:?} (sym={})", keycode, sym);
 
@@ -420,25 +741,58 @@ impl<'a> DaemonState<'a> {
             debug!("key pressed: keycode={:?} (no sym)", keycode);
 
             self.fallback_mode();
-            return;
+            return false;
         };
 
-        let chord = ChordDesc::new(keysym, self.kbd_state.modmask());
+        let chord =
+            ChordDesc::new(keysym, self.kbd_state.modmask(), self.kbd_state.ignore_mask());
         let mut drop_chain = true;
+        let mut matched = false;
         let mut mode_switch = None;
 
         if self.last_keypress + self.keypress_timeout < time {
             self.current_chain.clear();
+            self.count_prefix = None;
+        }
+
+        let numeric_prefix = self.modes[self.current_mode.index()].numeric_prefix();
+
+        if self.current_chain.is_empty() && numeric_prefix {
+            if let Some(digit) = chord.digit() {
+                let count =
+                    self.count_prefix.unwrap_or(0).saturating_mul(10).saturating_add(digit);
+                debug!("numeric prefix: {}", count);
+
+                self.count_prefix = Some(count);
+                self.last_keypress = time;
+                return true;
+            }
         }
 
+        // the release event of a key is only received if its press is consumed.
+        let mut release_chain = ChainDesc::default();
+        release_chain.push(
+            ChordDesc::new(keysym, self.kbd_state.modmask(), self.kbd_state.ignore_mask()));
+        let release_bound =
+            self.bindings.contains_key(&(self.current_mode, Trigger::Release, release_chain));
+
         self.current_chain.push(chord);
 
-        for (&(_, ref chain), cmd) in
-                self.bindings.iter().filter(|k| (k.0).0 == self.current_mode) {
+        let current_mode = self.current_mode;
+
+        for ((_, _, chain), cmd) in self.bindings
+                .iter()
+                .filter(|k| (k.0).0 == current_mode && (k.0).1 == Trigger::Press) {
             if self.current_chain.is_prefix_of(chain) {
+                matched = true;
+
                 if self.current_chain.len() == chain.len() {
                     info!("determined command {:?} from chain {:?}", cmd, self.current_chain);
-                    mode_switch = cmd.run();
+                    mode_switch = if let Some(count) = self.count_prefix {
+                        CmdDesc::Repeat { count, cmd: Box::new(cmd.clone()) }.run()
+                    } else {
+                        cmd.run()
+                    };
 
                     drop_chain = true;
                     break;
@@ -450,15 +804,91 @@ impl<'a> DaemonState<'a> {
 
         if drop_chain {
             self.current_chain.clear();
+            self.count_prefix = None;
         }
 
         if let Some(switch) = mode_switch {
-            self.switch_mode(switch);
-        } else {
+            if let Err(e) = self.switch_mode(switch) {
+                error!("could not switch modes: {}", e);
+            } else if self.previous_mode.is_some() {
+                self.temporary_key = Some(keycode);
+            }
+        } else if self.temporary_key.is_none() {
             self.fallback_mode();
         }
 
         self.last_keypress = time;
+
+        matched || release_bound
+    }
+
+    /// Process a chord determined from a key release event.
+    ///
+    /// Only bindings triggered by key release are considered, and the current chain is left
+    /// untouched. Releasing the key that set the current mode temporarily switches back to the
+    /// previous mode.
+    fn process_release(&mut self, keycode: Keycode) {
+        if self.temporary_key == Some(keycode) {
+            debug!("key setting the temporary mode released");
+            self.fallback_mode();
+            return;
+        }
+
+        let keysym = if let Some(sym) = self.kbd_state.lookup_keycode(keycode) {
+            debug!("key released: keycode={:?} (sym={})", keycode, sym);
+
+            sym
+        } else {
+            debug!("key released: keycode={:?} (no sym)", keycode);
+            return;
+        };
+
+        let mut chain = ChainDesc::default();
+        chain.push(
+            ChordDesc::new(keysym, self.kbd_state.modmask(), self.kbd_state.ignore_mask()));
+
+        let mode_switch = if let Some(cmd) =
+                self.bindings.get(&(self.current_mode, Trigger::Release, chain)) {
+            info!("determined command {:?} from released chord", cmd);
+            cmd.run()
+        } else {
+            return;
+        };
+
+        if let Some(switch) = mode_switch {
+            if let Err(e) = self.switch_mode(switch) {
+                error!("could not switch modes: {}", e);
+            }
+        }
+    }
+
+    /// Process a mouse button press event.
+    ///
+    /// The pointer position and the window below the pointer are passed to shell commands in
+    /// the `GWM_POINTER_X`, `GWM_POINTER_Y` and `GWM_POINTER_WINDOW` environment variables.
+    fn process_button(&mut self, event: &xproto::ButtonPressEvent) {
+        // the upper bits of the state hold the pointer buttons held down.
+        let modmask = xkb::ModMask(u32::from(event.state() & 0xff));
+        let button = ButtonDesc::new(event.detail(), modmask, self.kbd_state.ignore_mask());
+
+        let mode_switch = if let Some(cmd) = self.buttons.get(&button) {
+            info!("determined command {:?} from button {:?}", cmd, button);
+            let env = [
+                ("GWM_POINTER_X", event.root_x().to_string()),
+                ("GWM_POINTER_Y", event.root_y().to_string()),
+                ("GWM_POINTER_WINDOW", event.child().to_string()),
+            ];
+
+            cmd.run_with_env(&env)
+        } else {
+            return;
+        };
+
+        if let Some(switch) = mode_switch {
+            if let Err(e) = self.switch_mode(switch) {
+                error!("could not switch modes: {}", e);
+            }
+        }
     }
 
     /// Run the main loop of the daemon.
@@ -472,11 +902,20 @@ impl<'a> DaemonState<'a> {
         debug!("xkb base: {}", xkb_base);
 
         loop {
+            if signal::reload_requested() {
+                info!("reload requested");
+                self.reload_config();
+            }
+
             self.con().flush();
-            let event = if let Some(e) = self.con().wait_for_event() {
+            let event = if let Some(e) = self.con().poll_for_event() {
                 e
-            } else {
+            } else if self.con().has_error().is_err() {
                 return Err(XError::IOError.wrap());
+            } else {
+                // wait for the next event without missing reload requests in the meantime.
+                signal::wait_readable(self.con().as_raw_fd());
+                continue;
             };
 
             if event.response_type() == xkb_base {
@@ -494,17 +933,13 @@ impl<'a> DaemonState<'a> {
 
                         if event.changed() & xxkb::NKN_DETAIL_KEYCODES as u16 != 0 {
                             info!("updated keymap (new keyboard)");
-                            if let Err(e) = self.kbd_state.update_keymap() {
-                                e.handle();
-                            }
+                            self.update_keymap()?;
                         }
                     },
                     xxkb::MAP_NOTIFY => {
                         debug!("xkb event: MAP_NOTIFY");
 
-                        if let Err(e) = self.kbd_state.update_keymap() {
-                            e.handle();
-                        }
+                        self.update_keymap()?;
                     },
                     xxkb::STATE_NOTIFY => {
                         let event = unsafe { cast_event::<xxkb::StateNotifyEvent>(&event) };
@@ -518,15 +953,38 @@ impl<'a> DaemonState<'a> {
                 }
             } else {
                 match event.response_type() {
+                    0 => {
+                        let error = unsafe { cast_event::<GenericError>(&event) };
+
+                        log_protocol_error(error);
+                    },
                     xproto::KEY_PRESS => {
                         debug!("generic event: KEY_PRESS");
                         let event = unsafe { cast_event::<xproto::KeyPressEvent>(&event) };
                         let keycode = Keycode(u32::from(event.detail()));
 
-                        self.process_chord(keycode, event.time());
+                        // the keyboard is frozen until the key press is either consumed or
+                        // replayed to the focused client.
+                        let mode = if self.process_chord(keycode, event.time()) {
+                            xproto::ALLOW_ASYNC_KEYBOARD
+                        } else {
+                            xproto::ALLOW_REPLAY_KEYBOARD
+                        };
+
+                        xproto::allow_events(self.con(), mode as u8, event.time());
                     },
                     xproto::KEY_RELEASE => {
                         debug!("generic event: KEY_RELEASE");
+                        let event = unsafe { cast_event::<xproto::KeyReleaseEvent>(&event) };
+                        let keycode = Keycode(u32::from(event.detail()));
+
+                        self.process_release(keycode);
+                    },
+                    xproto::BUTTON_PRESS => {
+                        debug!("generic event: BUTTON_PRESS");
+                        let event = unsafe { cast_event::<xproto::ButtonPressEvent>(&event) };
+
+                        self.process_button(event);
                     },
                     t => {
                         debug!("generic event (unknown): {}", t);
@@ -33,6 +33,7 @@
  */
 
 extern crate getopts;
+extern crate libc;
 #[macro_use]
 extern crate log;
 extern crate toml;
@@ -54,6 +54,7 @@ use xkb::context::Context;
 use xkb::x11 as x11;
 
 use gwm_kbd::kbd::err::{KbdError, KbdResult, XError};
+use gwm_kbd::kbd::signal;
 use gwm_kbd::kbd::state::{DaemonState, KbdState};
 
 /// Initialize the logger.
@@ -65,9 +66,44 @@ fn setup_logger() {
     env::remove_var("RUST_LOG");
 }
 
+/// Get the value of an environment variable holding an absolute path, if it is set.
+///
+/// Empty and relative paths are ignored, as the XDG base directory specification demands.
+fn env_path(var: &str) -> Option<PathBuf> {
+    env::var_os(var).map(PathBuf::from).filter(|p| p.is_absolute())
+}
+
+/// Determine the config file to use if none has been given on the command line.
+///
+/// Uses `$XDG_CONFIG_HOME/gwm/gwmkbdrc.toml` (or `~/.config/gwm/gwmkbdrc.toml`) if it
+/// exists, and `~/.gwmkbdrc` otherwise. Needs to be called after the logger is set up.
+fn default_config_path() -> PathBuf {
+    let home = env_path("HOME");
+
+    let config_home = env_path("XDG_CONFIG_HOME")
+        .or_else(|| home.as_ref().map(|h| h.join(".config")));
+
+    if let Some(mut buf) = config_home {
+        buf.push("gwm");
+        buf.push("gwmkbdrc.toml");
+
+        if buf.exists() {
+            return buf;
+        }
+    }
+
+    if let Some(mut buf) = home {
+        buf.push(".gwmkbdrc");
+        buf
+    } else {
+        warn!("couldn't determine the value of $HOME, using current dir");
+        PathBuf::from("gwmkbdrc")
+    }
+}
+
 /// Main routine.
-fn do_main(path: &Path) -> KbdResult<()> {
-    let (con, screen_num) = match Connection::connect(None) {
+fn do_main(path: &Path, strict: bool, display: Option<&str>) -> KbdResult<()> {
+    let (con, screen_num) = match Connection::connect(display) {
         Ok(c) => c,
         Err(e) => {
             return Err(XError::CouldNotConnect(e).wrap());
@@ -150,25 +186,33 @@ fn do_main(path: &Path) -> KbdResult<()> {
         }
     };
 
-    // TODO: proper error handling
-    cookie.request_check().expect("no events selected");
+    if let Err(e) = cookie.request_check() {
+        return Err(XError::CouldNotSelectEvents(e).wrap());
+    }
 
+    // held keys don't generate release events when autorepeat is detectable, which is needed
+    // to end temporary mode switches only when the activating key is released.
     let flags =
         xxkb::PER_CLIENT_FLAG_GRABS_USE_XKB_STATE |
-        xxkb::PER_CLIENT_FLAG_LOOKUP_STATE_WHEN_GRABBED;
+        xxkb::PER_CLIENT_FLAG_LOOKUP_STATE_WHEN_GRABBED |
+        xxkb::PER_CLIENT_FLAG_DETECTABLE_AUTO_REPEAT;
 
     let cookie =
         xxkb::per_client_flags(&con, xxkb::ID_USE_CORE_KBD as u16, flags, flags, 0, 0, 0);
 
-    // TODO: proper error handling
-    cookie.get_reply().expect("no flags set");
+    if let Err(e) = cookie.get_reply() {
+        return Err(XError::CouldNotSetPerClientFlags(e).wrap());
+    }
 
     let kbd_state = KbdState::new(&con, screen_num, keymap, state)?;
     let mut daemon_state =
-        DaemonState::from_config(path, kbd_state)?;
+        DaemonState::from_config(path, kbd_state, strict)?;
     debug!("initial daemon state: {:?}", daemon_state);
 
+    signal::install_reload_handler();
+
     daemon_state.grab_current_mode();
+    daemon_state.grab_buttons();
     daemon_state.run()
 }
 
@@ -178,7 +222,13 @@ fn main() {
     // set up option parsing
     let mut opts = Options::new();
     opts.optopt("c", "config", "set config file name", "FILE");
+    opts.optopt("",
+                "strict",
+                "refuse to start on duplicate bindings, true or false (default: true)",
+                "BOOL");
+    opts.optopt("", "display", "connect to DISPLAY instead of $DISPLAY", "DISPLAY");
     opts.optflag("h", "help", "print this help menu");
+    opts.optflag("", "version", "print version information");
 
     // match on args and decide what to do
     let matches = match opts.parse(&args[1..]) {
@@ -192,19 +242,33 @@ fn main() {
         return;
     }
 
+    if matches.opt_present("version") {
+        println!("gwm-kbd {}", env!("CARGO_PKG_VERSION"));
+        return;
+    }
+
+    setup_logger();
+
+    let strict = match matches.opt_str("strict") {
+        Some(value) => value.parse().unwrap_or_else(|_| {
+            KbdError::InvalidOptionValue {
+                option: "strict".to_owned(),
+                value,
+                expected: "true or false",
+            }.handle()
+        }),
+        None => true,
+    };
+
     let config_path = if let Some(p) = matches.opt_str("c") {
         p.into()
-    } else if let Some(mut buf) = env::home_dir() {
-        buf.push(".gwmkbdrc");
-        buf
     } else {
-        warn!("couldn't determine the value of $HOME, using current dir");
-        PathBuf::from("gwmkbdrc")
+        default_config_path()
     };
 
-    setup_logger();
+    let display = matches.opt_str("display");
 
-    match do_main(&config_path) {
+    match do_main(&config_path, strict, display.as_deref()) {
         Ok(()) => ::std::process::exit(0),
         Err(e) => e.handle(),
     }